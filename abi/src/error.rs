@@ -0,0 +1,325 @@
+//! Typed errors for the prediction market contract
+//!
+//! Every failure path of the contract maps to a `PredictionMarketError` variant with a
+//! stable machine-readable code. Errors cross the contract boundary as
+//! `ContractError::Other("<CODE>: <message>")`, so clients (and the GraphQL service) can
+//! recover the kind with `PredictionMarketError::split_code` instead of matching on English text.
+
+use crate::MarketId;
+use linera_sdk::base::{Amount, ContractError};
+use linera_sdk::views::ViewError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Every way a prediction market operation can fail.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum PredictionMarketError {
+    /// The operation requires an authenticated signer
+    Unauthorized,
+    /// Market question is empty
+    EmptyQuestion,
+    /// Market resolution criteria are empty
+    EmptyResolutionCriteria,
+    /// Evidence submitted with an answer is empty
+    EmptyEvidence,
+    /// Fewer than two options were supplied
+    TooFewOptions,
+    /// More options were supplied than the application parameters allow
+    TooManyOptions,
+    /// Market duration exceeds the application's maximum
+    DurationTooLong,
+    /// Max reward must be greater than zero
+    InvalidMaxReward,
+    /// Bet amount must be greater than zero
+    InvalidAmount,
+    /// Bet amount is below the application's minimum bet
+    BetTooSmall,
+    /// Bet amount is above the application's maximum bet
+    BetTooLarge,
+    /// Market ID is not in the canonical `<chain id>:<index>` form
+    InvalidMarketId(String),
+    /// No market with this ID exists on this chain
+    MarketNotFound(MarketId),
+    /// Market is not accepting bets
+    MarketNotActive,
+    /// Market end time has passed
+    MarketEnded,
+    /// Betting window of the market has closed
+    BettingClosed,
+    /// Lock window must be shorter than the market duration
+    InvalidLockWindow,
+    /// Market cannot be locked before its lock time
+    LockNotDue,
+    /// Market end time has not passed yet
+    MarketNotEnded,
+    /// Option is not one of the market options
+    InvalidOption(String),
+    /// User has no bet on this market
+    NoBet,
+    /// Caller is not the market's designated resolver
+    NotResolver,
+    /// Resolver settings cannot work: a committee with duplicate members or an unreachable
+    /// quorum, an empty voting window, or a price condition not fitting the market
+    InvalidResolver,
+    /// Market has already been resolved
+    AlreadyResolved,
+    /// Market has not been resolved yet
+    NotResolved,
+    /// Market has been cancelled
+    MarketCancelled,
+    /// Challenge bond is zero, the challenge window is empty, the resolver cannot be disputed,
+    /// or the arbiter is the creator or one of the resolvers
+    InvalidDisputeSettings,
+    /// An answer has been proposed and is awaiting finalization or arbitration
+    ResolutionPending,
+    /// Market has no proposed answer awaiting finalization or dispute
+    NotProposed,
+    /// The challenge window of the proposed answer is still open
+    ChallengeWindowOpen,
+    /// The challenge window of the proposed answer has closed
+    ChallengeWindowClosed,
+    /// Market has no disputed answer to arbitrate
+    NotDisputed,
+    /// Caller is not the market's arbiter
+    NotArbiter,
+    /// Market is not resolved by bettor vote
+    NoVoting,
+    /// The voting window is still open
+    VotingOpen,
+    /// The voting window has closed
+    VotingClosed,
+    /// Market is not resolved from a price feed
+    NoPriceFeed,
+    /// The feed has no price published at or after the observation time yet
+    PriceNotAvailable,
+    /// Calling the price-feed application failed
+    OracleCallFailed(String),
+    /// Resolution deadline is empty, or falls before the voting window or observation time
+    InvalidResolutionDeadline,
+    /// The resolution deadline has passed; the market can only be expired
+    ResolutionDeadlinePassed,
+    /// The resolution deadline has not passed yet, or the market has none
+    DeadlineNotReached,
+    /// Market expired without resolution
+    MarketExpired,
+    /// Caller may not cancel this market
+    CancelNotAllowed,
+    /// Market stakes are not refundable, or the refund was already paid
+    NotRefundable,
+    /// User's bet is not on the winning option
+    NotWinner,
+    /// Reward has already been claimed
+    AlreadyClaimed,
+    /// There is nothing to claim for this user
+    NothingToClaim,
+    /// A token transfer into or out of the escrow failed
+    TransferFailed { amount: Amount, reason: String },
+    /// An amount or time calculation overflowed
+    ArithmeticOverflow,
+    /// Computed rewards would exceed the market pool
+    PayoutExceedsPool,
+    /// Fee in basis points must not exceed 10000
+    InvalidFee,
+    /// Creator fee exceeds the application's cap
+    CreatorFeeTooHigh,
+    /// Owner has no accrued fees to withdraw
+    NoFees,
+    /// The application has no treasury to receive the pool
+    NoTreasury,
+    /// Caller is not an admin
+    NotAdmin,
+    /// Market creation is paused by an admin
+    CreationPaused,
+    /// Betting is paused by an admin, on this chain or on this market
+    BettingPaused,
+    /// Removing this admin would leave the chain without one
+    LastAdmin,
+    /// Position holds less stake on the option than requested
+    InsufficientStake,
+    /// Position holds bets placed from another chain
+    WrongOrigin,
+    /// Application parameters or instantiation argument are inconsistent
+    InvalidParameters(String),
+    /// Stored state was written by a newer version of the contract
+    UnsupportedStateVersion(u32),
+    /// Reading or writing the application state failed
+    Storage(String),
+}
+
+impl PredictionMarketError {
+    /// Stable code identifying the error kind. Codes never change once published.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::EmptyQuestion => "EMPTY_QUESTION",
+            Self::EmptyResolutionCriteria => "EMPTY_RESOLUTION_CRITERIA",
+            Self::EmptyEvidence => "EMPTY_EVIDENCE",
+            Self::TooFewOptions => "TOO_FEW_OPTIONS",
+            Self::TooManyOptions => "TOO_MANY_OPTIONS",
+            Self::DurationTooLong => "DURATION_TOO_LONG",
+            Self::InvalidMaxReward => "INVALID_MAX_REWARD",
+            Self::InvalidAmount => "INVALID_AMOUNT",
+            Self::BetTooSmall => "BET_TOO_SMALL",
+            Self::BetTooLarge => "BET_TOO_LARGE",
+            Self::InvalidMarketId(_) => "INVALID_MARKET_ID",
+            Self::MarketNotFound(_) => "MARKET_NOT_FOUND",
+            Self::MarketNotActive => "MARKET_NOT_ACTIVE",
+            Self::MarketEnded => "MARKET_ENDED",
+            Self::BettingClosed => "BETTING_CLOSED",
+            Self::InvalidLockWindow => "INVALID_LOCK_WINDOW",
+            Self::LockNotDue => "LOCK_NOT_DUE",
+            Self::MarketNotEnded => "MARKET_NOT_ENDED",
+            Self::InvalidOption(_) => "INVALID_OPTION",
+            Self::NoBet => "NO_BET",
+            Self::NotResolver => "NOT_RESOLVER",
+            Self::InvalidResolver => "INVALID_RESOLVER",
+            Self::AlreadyResolved => "ALREADY_RESOLVED",
+            Self::NotResolved => "NOT_RESOLVED",
+            Self::MarketCancelled => "MARKET_CANCELLED",
+            Self::InvalidDisputeSettings => "INVALID_DISPUTE_SETTINGS",
+            Self::ResolutionPending => "RESOLUTION_PENDING",
+            Self::NotProposed => "NOT_PROPOSED",
+            Self::ChallengeWindowOpen => "CHALLENGE_WINDOW_OPEN",
+            Self::ChallengeWindowClosed => "CHALLENGE_WINDOW_CLOSED",
+            Self::NotDisputed => "NOT_DISPUTED",
+            Self::NotArbiter => "NOT_ARBITER",
+            Self::NoVoting => "NO_VOTING",
+            Self::VotingOpen => "VOTING_OPEN",
+            Self::VotingClosed => "VOTING_CLOSED",
+            Self::NoPriceFeed => "NO_PRICE_FEED",
+            Self::PriceNotAvailable => "PRICE_NOT_AVAILABLE",
+            Self::OracleCallFailed(_) => "ORACLE_CALL_FAILED",
+            Self::InvalidResolutionDeadline => "INVALID_RESOLUTION_DEADLINE",
+            Self::ResolutionDeadlinePassed => "RESOLUTION_DEADLINE_PASSED",
+            Self::DeadlineNotReached => "DEADLINE_NOT_REACHED",
+            Self::MarketExpired => "MARKET_EXPIRED",
+            Self::CancelNotAllowed => "CANCEL_NOT_ALLOWED",
+            Self::NotRefundable => "NOT_REFUNDABLE",
+            Self::NotWinner => "NOT_WINNER",
+            Self::AlreadyClaimed => "ALREADY_CLAIMED",
+            Self::NothingToClaim => "NOTHING_TO_CLAIM",
+            Self::TransferFailed { .. } => "TRANSFER_FAILED",
+            Self::ArithmeticOverflow => "ARITHMETIC_OVERFLOW",
+            Self::PayoutExceedsPool => "PAYOUT_EXCEEDS_POOL",
+            Self::InvalidFee => "INVALID_FEE",
+            Self::CreatorFeeTooHigh => "CREATOR_FEE_TOO_HIGH",
+            Self::NoFees => "NO_FEES",
+            Self::NoTreasury => "NO_TREASURY",
+            Self::NotAdmin => "NOT_ADMIN",
+            Self::CreationPaused => "CREATION_PAUSED",
+            Self::BettingPaused => "BETTING_PAUSED",
+            Self::LastAdmin => "LAST_ADMIN",
+            Self::InsufficientStake => "INSUFFICIENT_STAKE",
+            Self::WrongOrigin => "WRONG_ORIGIN",
+            Self::InvalidParameters(_) => "INVALID_PARAMETERS",
+            Self::UnsupportedStateVersion(_) => "UNSUPPORTED_STATE_VERSION",
+            Self::Storage(_) => "STORAGE_ERROR",
+        }
+    }
+
+    /// Split a contract error message of the form `"<CODE>: <message>"` into its code and message.
+    ///
+    /// Returns `None` for messages that did not originate from a `PredictionMarketError`.
+    pub fn split_code(message: &str) -> Option<(&str, &str)> {
+        let (code, rest) = message.split_once(": ")?;
+        let is_code = !code.is_empty()
+            && code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+        is_code.then_some((code, rest))
+    }
+}
+
+impl fmt::Display for PredictionMarketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.code())?;
+        match self {
+            Self::Unauthorized => write!(f, "No authenticated signer"),
+            Self::EmptyQuestion => write!(f, "Question cannot be empty"),
+            Self::EmptyResolutionCriteria => write!(f, "Resolution criteria cannot be empty"),
+            Self::EmptyEvidence => write!(f, "Evidence cannot be empty"),
+            Self::TooFewOptions => write!(f, "At least 2 options required"),
+            Self::TooManyOptions => write!(f, "Too many options"),
+            Self::DurationTooLong => write!(f, "Market duration exceeds the maximum"),
+            Self::InvalidMaxReward => write!(f, "Max reward must be at least the minimum bet and greater than 0"),
+            Self::InvalidAmount => write!(f, "Bet amount must be greater than 0"),
+            Self::BetTooSmall => write!(f, "Bet amount is below the minimum bet"),
+            Self::BetTooLarge => write!(f, "Bet amount is above the maximum bet"),
+            Self::InvalidMarketId(id) => write!(f, "Invalid market ID: {}", id),
+            Self::MarketNotFound(id) => write!(f, "Market {} not found", id),
+            Self::MarketNotActive => write!(f, "Market is not active"),
+            Self::MarketEnded => write!(f, "Market has ended"),
+            Self::BettingClosed => write!(f, "Betting on this market has closed"),
+            Self::InvalidLockWindow => write!(f, "Lock window must be shorter than the market duration"),
+            Self::LockNotDue => write!(f, "Market cannot be locked before its lock time"),
+            Self::MarketNotEnded => write!(f, "Market has not ended yet"),
+            Self::InvalidOption(option) => write!(f, "Invalid option: {}", option),
+            Self::NoBet => write!(f, "User has no bet on this market"),
+            Self::NotResolver => write!(f, "Only the market's resolver can resolve it"),
+            Self::InvalidResolver => write!(f, "Invalid resolver: a committee needs distinct members and a quorum between 1 and their number, a vote a non-empty window, a price feed two options and an observation after betting closes"),
+            Self::AlreadyResolved => write!(f, "Market already resolved"),
+            Self::NotResolved => write!(f, "Market is not resolved"),
+            Self::MarketCancelled => write!(f, "Market has been cancelled"),
+            Self::InvalidDisputeSettings => {
+                write!(f, "Dispute settings need a bond, a challenge window, an owner resolver and an independent arbiter")
+            }
+            Self::ResolutionPending => write!(f, "Market has a proposed answer awaiting finalization"),
+            Self::NotProposed => write!(f, "Market has no proposed answer"),
+            Self::ChallengeWindowOpen => write!(f, "Challenge window is still open"),
+            Self::ChallengeWindowClosed => write!(f, "Challenge window has closed"),
+            Self::NotDisputed => write!(f, "Market resolution is not disputed"),
+            Self::NotArbiter => write!(f, "Only the market's arbiter can settle the dispute"),
+            Self::NoVoting => write!(f, "Market is not resolved by bettor vote"),
+            Self::VotingOpen => write!(f, "Voting window is still open"),
+            Self::VotingClosed => write!(f, "Voting window has closed"),
+            Self::NoPriceFeed => write!(f, "Market is not resolved from a price feed"),
+            Self::PriceNotAvailable => write!(f, "No price published at or after the observation time yet"),
+            Self::OracleCallFailed(reason) => write!(f, "Price feed call failed: {}", reason),
+            Self::InvalidResolutionDeadline => {
+                write!(f, "Resolution deadline must leave time for voting or the price observation")
+            }
+            Self::ResolutionDeadlinePassed => write!(f, "Resolution deadline has passed"),
+            Self::DeadlineNotReached => write!(f, "Resolution deadline has not passed"),
+            Self::MarketExpired => write!(f, "Market expired without resolution"),
+            Self::CancelNotAllowed => {
+                write!(f, "Only the creator before the first bet, or an admin, can cancel a market")
+            }
+            Self::NotRefundable => write!(f, "Market stakes are not refundable"),
+            Self::NotWinner => write!(f, "User did not win this market"),
+            Self::AlreadyClaimed => write!(f, "Reward already claimed"),
+            Self::NothingToClaim => write!(f, "No reward available"),
+            Self::TransferFailed { amount, reason } => {
+                write!(f, "Token transfer of {} failed: {}", amount, reason)
+            }
+            Self::ArithmeticOverflow => write!(f, "Arithmetic overflow"),
+            Self::PayoutExceedsPool => write!(f, "Computed rewards exceed the market pool"),
+            Self::InvalidFee => write!(f, "Fee in basis points must not exceed 10000"),
+            Self::CreatorFeeTooHigh => write!(f, "Creator fee exceeds the maximum creator fee"),
+            Self::NoFees => write!(f, "No accrued fees to withdraw"),
+            Self::NoTreasury => write!(f, "The application has no treasury"),
+            Self::NotAdmin => write!(f, "Only admins can perform this operation"),
+            Self::CreationPaused => write!(f, "Market creation is paused"),
+            Self::BettingPaused => write!(f, "Betting is paused"),
+            Self::LastAdmin => write!(f, "Cannot remove the last admin"),
+            Self::InsufficientStake => write!(f, "Position holds less stake on this option than requested"),
+            Self::WrongOrigin => write!(f, "Position holds bets placed from another chain"),
+            Self::InvalidParameters(reason) => write!(f, "Invalid parameters: {}", reason),
+            Self::UnsupportedStateVersion(version) => {
+                write!(f, "State version {} is newer than this contract supports", version)
+            }
+            Self::Storage(reason) => write!(f, "Storage error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for PredictionMarketError {}
+
+impl From<ViewError> for PredictionMarketError {
+    fn from(error: ViewError) -> Self {
+        PredictionMarketError::Storage(error.to_string())
+    }
+}
+
+impl From<PredictionMarketError> for ContractError {
+    fn from(error: PredictionMarketError) -> Self {
+        ContractError::Other(error.to_string())
+    }
+}
@@ -0,0 +1,510 @@
+//! Prediction Market ABI
+//!
+//! Wire format of the prediction market application: parameters, messages, effects, the
+//! stored data types returned in query snapshots, and error codes. The contract, the
+//! GraphQL service and third-party clients all depend on this crate, so the format is
+//! defined in one place.
+
+use linera_sdk::base::{Amount, ApplicationId, ChainId, ContractAbi, Owner, ServiceAbi, Timestamp};
+use serde::{Deserialize, Serialize};
+
+mod error;
+mod market_id;
+pub mod payout;
+mod types;
+
+pub use error::PredictionMarketError;
+pub use market_id::MarketId;
+pub use types::{
+    Bet, Claimable, Comparator, DisputeSettings, Evidence, FeeBalance, Market, MarketStatus, MarketSummary,
+    NoWinnerPolicy, OutgoingBet, Position, PredictionMarketSnapshot, PriceCondition, Proposal, RegistryPage,
+    Resolver, SurplusPolicy, Vote, Withdrawal,
+};
+
+/// ABI of the prediction market application.
+pub struct PredictionMarketAbi;
+
+impl ContractAbi for PredictionMarketAbi {
+    type Message = PredictionMarketMessage;
+    type Effect = PredictionMarketEffect;
+    type Parameters = PredictionMarketParameters;
+    type InstantiationArgument = PredictionMarketInstantiationArgument;
+}
+
+/// Queries are a JSON-serialized `PredictionMarketQuery`, answered in JSON. An empty query
+/// is answered with the `PredictionMarketSnapshot`.
+impl ServiceAbi for PredictionMarketAbi {
+    type Query = String;
+    type QueryResponse = String;
+}
+
+/// Query answered by the contract's service handler.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PredictionMarketQuery {
+    /// The whole state of this chain, answered with a `PredictionMarketSnapshot`
+    Snapshot,
+    /// One page of the global market index, read straight from the registry and answered
+    /// with a `RegistryPage`
+    RegistryPage { offset: u64, limit: u64 },
+}
+
+/// Parameters shared by every chain running the application.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct PredictionMarketParameters {
+    /// Chain keeping the global market index. Every chain announces its markets there.
+    pub registry_chain: Option<ChainId>,
+    /// Bond escrowed from the creator of every market. It is returned once the market is
+    /// resolved or cancelled cleanly, and shared between the bettors if the creator is at fault.
+    pub creator_bond: Amount,
+    /// Protocol fee in basis points
+    pub protocol_fee_bps: u16,
+    /// Highest creator fee, in basis points, a market may set
+    pub max_creator_fee_bps: u16,
+    /// Smallest accepted bet
+    pub min_bet: Amount,
+    /// Largest accepted bet, if bets are capped
+    pub max_bet: Option<Amount>,
+    /// Largest number of options per market, if capped
+    pub max_options: Option<u32>,
+    /// Longest market duration in minutes, if capped
+    pub max_duration_minutes: Option<u64>,
+    /// Owner with admin rights on every chain running the application
+    pub admin: Option<Owner>,
+    /// Owner receiving protocol fees
+    pub treasury: Option<Owner>,
+    /// Token stakes are paid in; `None` is the chain's native token, the only one
+    /// the escrow currently supports
+    pub token: Option<ApplicationId>,
+}
+
+impl PredictionMarketParameters {
+    /// Fail unless every limit is consistent
+    pub fn validate(&self) -> Result<(), PredictionMarketError> {
+        let invalid = |reason: &str| Err(PredictionMarketError::InvalidParameters(reason.to_string()));
+        let total_fee_bps = u32::from(self.protocol_fee_bps) + u32::from(self.max_creator_fee_bps);
+        if total_fee_bps > u32::from(payout::MAX_BPS) {
+            return invalid("protocol and creator fees exceed 10000 basis points");
+        }
+        if self.protocol_fee_bps > 0 && self.treasury.is_none() {
+            return invalid("a protocol fee requires a treasury");
+        }
+        if self.max_bet.is_some_and(|max_bet| max_bet < self.min_bet || max_bet == Amount::ZERO) {
+            return invalid("max bet is below the min bet");
+        }
+        if self.max_options.is_some_and(|max_options| max_options < 2) {
+            return invalid("max options is below 2");
+        }
+        if self.max_duration_minutes == Some(0) {
+            return invalid("max duration is zero");
+        }
+        if self.token.is_some() {
+            return invalid("only the native token is supported");
+        }
+        Ok(())
+    }
+}
+
+/// Argument given when the application is instantiated.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct PredictionMarketInstantiationArgument {
+    /// Initial admins of the creating chain. If empty, the instantiating owner becomes
+    /// the only admin.
+    pub admins: Vec<Owner>,
+}
+
+/// Call made by other applications on the same chain.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PredictionMarketCall {
+    /// Resolve a market whose resolver is the calling application (`Resolver::Oracle`).
+    /// Answered with the effects of the resolution.
+    ResolveMarket {
+        market_id: MarketId,
+        correct_answer: String,
+        /// Evidence backing the answer, kept on the market
+        evidence: Evidence,
+    },
+}
+
+/// Message for the prediction market application.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PredictionMarketMessage {
+    /// Create a new prediction market
+    CreateMarket {
+        question: String,
+        description: String,
+        /// How the correct answer will be determined
+        resolution_criteria: String,
+        /// Where the resolver will look for the answer
+        resolution_source: String,
+        duration_minutes: u64,
+        options: Vec<String>,
+        max_reward: Amount,
+        /// Close betting this many minutes before the end time
+        lock_before_end: Option<u64>,
+        /// What happens to the pool if nobody bet on the correct answer
+        no_winner_policy: NoWinnerPolicy,
+        /// What happens to the pool amount withheld by the `max_reward` cap
+        surplus_policy: SurplusPolicy,
+        /// Fee in basis points kept in the pool when a bet is withdrawn
+        withdrawal_fee_bps: u16,
+        /// Fee in basis points paid to the creator out of the pool at resolution
+        creator_fee_bps: u16,
+        /// Who decides the correct answer
+        resolver: Resolver,
+        /// Challenge window and bonds; without them the resolver's answer is final immediately
+        dispute_settings: Option<DisputeSettings>,
+        /// Minutes after the end time by which the market must be resolved, or it can be expired
+        resolution_deadline_minutes: Option<u64>,
+    },
+    /// Place a bet on a market option, adding to the user's position
+    PlaceBet {
+        market_id: MarketId,
+        option: String,
+        amount: Amount,
+    },
+    /// Place a bet on a market hosted on another chain, sending the stake along
+    PlaceRemoteBet {
+        market_id: MarketId,
+        option: String,
+        amount: Amount,
+    },
+    /// Withdraw part or all of the stake on an option while the market is active
+    WithdrawBet {
+        market_id: MarketId,
+        option: String,
+        amount: Amount,
+    },
+    /// Withdraw stake placed with `PlaceRemoteBet`; the market chain sends the amount back
+    WithdrawRemoteBet {
+        market_id: MarketId,
+        option: String,
+        amount: Amount,
+    },
+    /// Resolve a market, or submit a committee member's answer (designated resolver only)
+    ResolveMarket {
+        market_id: MarketId,
+        correct_answer: String,
+        /// Evidence backing the answer, kept on the market
+        evidence: Evidence,
+    },
+    /// Dispute a proposed answer during its challenge window, posting the market's bond
+    DisputeResolution {
+        market_id: MarketId,
+    },
+    /// Make an undisputed proposed answer final once its challenge window closed (anyone can send this)
+    FinalizeResolution {
+        market_id: MarketId,
+    },
+    /// Rule on a disputed answer (arbiter only); the losing side's bond goes to the winning side
+    ArbitrateMarket {
+        market_id: MarketId,
+        correct_answer: String,
+        /// Evidence backing the ruling, kept on the market in place of the proposer's
+        evidence: Evidence,
+    },
+    /// Vote on the correct answer of a market resolved by bettor vote, weighted by the
+    /// voter's stake; voting again replaces the previous vote
+    CastVote {
+        market_id: MarketId,
+        option: String,
+    },
+    /// Vote with stake placed with `PlaceRemoteBet` on a market resolved by bettor vote
+    CastRemoteVote {
+        market_id: MarketId,
+        option: String,
+    },
+    /// Resolve a market by the plurality of bettor votes once voting closed (anyone can send this)
+    CloseVote {
+        market_id: MarketId,
+    },
+    /// Resolve a price-feed market from the price observed at its observation time (anyone can send this)
+    ResolveFromFeed {
+        market_id: MarketId,
+    },
+    /// Refund every bettor of a market left unresolved past its deadline (anyone can send this)
+    ExpireMarket {
+        market_id: MarketId,
+    },
+    /// Claim reward from a resolved market
+    ClaimReward {
+        market_id: MarketId,
+    },
+    /// Claim the reward of stake placed with `PlaceRemoteBet`; the market chain sends it back
+    ClaimRemoteReward {
+        market_id: MarketId,
+    },
+    /// Lock a market whose betting window has closed (anyone can send this)
+    LockMarket {
+        market_id: MarketId,
+    },
+    /// Cancel a market (creator before the first bet, or admin before resolution)
+    CancelMarket {
+        market_id: MarketId,
+        /// Slash the creator bond to the bettors (admin only)
+        at_fault: bool,
+    },
+    /// Claim back the stake placed on a cancelled or expired market, or on a market nobody won
+    ClaimRefund {
+        market_id: MarketId,
+    },
+    /// Claim the refund of stake placed with `PlaceRemoteBet`; the market chain sends it back
+    ClaimRemoteRefund {
+        market_id: MarketId,
+    },
+    /// Withdraw every protocol or creator fee accrued to the signer
+    WithdrawFees,
+    /// Pause or resume market creation on this chain (admin only)
+    SetCreationPaused {
+        paused: bool,
+    },
+    /// Pause or resume betting on every market of this chain (admin only)
+    SetBettingPaused {
+        paused: bool,
+    },
+    /// Pause or resume betting on one market (admin only)
+    SetMarketBettingPaused {
+        market_id: MarketId,
+        paused: bool,
+    },
+    /// Cancel any unresolved market, including one whose answer is proposed or disputed
+    /// (admin only)
+    ForceCancelMarket {
+        market_id: MarketId,
+        /// Slash the creator bond to the bettors
+        at_fault: bool,
+    },
+    /// Grant admin rights on this chain (admin only)
+    AddAdmin {
+        owner: Owner,
+    },
+    /// Revoke admin rights on this chain (admin only)
+    RemoveAdmin {
+        owner: Owner,
+    },
+}
+
+/// Effect for the prediction market application.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PredictionMarketEffect {
+    /// Market created event
+    MarketCreated {
+        market_id: MarketId,
+        creator: Owner,
+    },
+    /// Bet placed event
+    BetPlaced {
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+    },
+    /// Cross-chain bet sent to the market chain, with its stake already transferred
+    /// to the application escrow there
+    RemoteBet {
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+        origin: ChainId,
+    },
+    /// Cross-chain bet recorded by the market chain
+    RemoteBetAccepted {
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+    },
+    /// Cross-chain bet refused by the market chain; the stake was sent back to the user
+    RemoteBetRejected {
+        market_id: MarketId,
+        user: Owner,
+        amount: Amount,
+        /// Code of the `PredictionMarketError` that refused the bet
+        code: String,
+    },
+    /// Cross-chain withdrawal sent to the market chain by the chain the bets were placed from
+    RemoteWithdraw {
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+    },
+    /// Cross-chain vote sent to the market chain by the chain the bets were placed from
+    RemoteVote {
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+    },
+    /// Cross-chain reward claim sent to the market chain by the chain the bets were placed from
+    RemoteClaim {
+        market_id: MarketId,
+        user: Owner,
+    },
+    /// Cross-chain refund claim sent to the market chain by the chain the bets were placed from
+    RemoteRefund {
+        market_id: MarketId,
+        user: Owner,
+    },
+    /// Cross-chain withdrawal, vote or claim refused by the market chain
+    RemoteRequestRejected {
+        market_id: MarketId,
+        user: Owner,
+        /// Code of the `PredictionMarketError` that refused the request
+        code: String,
+    },
+    /// Bet withdrawn event
+    BetWithdrawn {
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+        fee: Amount,
+    },
+    /// Market locked event
+    MarketLocked {
+        market_id: MarketId,
+    },
+    /// Answer submitted by a resolver committee member, before quorum is reached
+    ResolutionVoteCast {
+        market_id: MarketId,
+        voter: Owner,
+        answer: String,
+    },
+    /// Answer proposed by the resolver, final unless disputed before `challenge_ends`
+    ResolutionProposed {
+        market_id: MarketId,
+        proposer: Owner,
+        answer: String,
+        bond: Amount,
+        challenge_ends: Timestamp,
+    },
+    /// Proposed answer disputed, awaiting the arbiter
+    ResolutionDisputed {
+        market_id: MarketId,
+        disputer: Owner,
+        bond: Amount,
+    },
+    /// Bond of an undisputed proposal returned to the proposer
+    BondReturned {
+        market_id: MarketId,
+        owner: Owner,
+        amount: Amount,
+    },
+    /// Dispute settled by the arbiter; `amount` is both bonds, paid to the winning side
+    DisputeSettled {
+        market_id: MarketId,
+        winner: Owner,
+        amount: Amount,
+    },
+    /// Bettor vote recorded
+    VoteCast {
+        market_id: MarketId,
+        voter: Owner,
+        option: String,
+        weight: Amount,
+    },
+    /// Bettor vote ended in a tie, or without any vote; the market is cancelled and refunded
+    VoteTied {
+        market_id: MarketId,
+    },
+    /// Price read from the feed to resolve a price-feed market
+    PriceObserved {
+        market_id: MarketId,
+        price: u128,
+        timestamp: Timestamp,
+    },
+    /// Market resolved event
+    MarketResolved {
+        market_id: MarketId,
+        correct_answer: String,
+    },
+    /// Reward claimed event
+    RewardClaimed {
+        market_id: MarketId,
+        user: Owner,
+        amount: Amount,
+    },
+    /// Pool of a market without winners sent to the treasury
+    PoolSentToTreasury {
+        market_id: MarketId,
+        treasury: Owner,
+        amount: Amount,
+    },
+    /// Protocol and creator fees taken off the pool at resolution
+    FeesAccrued {
+        market_id: MarketId,
+        protocol_fee: Amount,
+        creator_fee: Amount,
+    },
+    /// Accrued fees paid out to their recipient
+    FeesWithdrawn {
+        owner: Owner,
+        amount: Amount,
+    },
+    /// Market creation paused or resumed
+    CreationPauseSet {
+        paused: bool,
+        admin: Owner,
+    },
+    /// Betting paused or resumed on every market of the chain
+    BettingPauseSet {
+        paused: bool,
+        admin: Owner,
+    },
+    /// Betting paused or resumed on one market
+    MarketBettingPauseSet {
+        market_id: MarketId,
+        paused: bool,
+        admin: Owner,
+    },
+    /// Market cancelled by an admin regardless of its resolution progress
+    MarketForceCancelled {
+        market_id: MarketId,
+        admin: Owner,
+    },
+    /// Admin rights granted
+    AdminAdded {
+        owner: Owner,
+        added_by: Owner,
+    },
+    /// Admin rights revoked
+    AdminRemoved {
+        owner: Owner,
+        removed_by: Owner,
+    },
+    /// Pool amount withheld by the `max_reward` cap paid out
+    SurplusPaid {
+        market_id: MarketId,
+        recipient: Owner,
+        amount: Amount,
+    },
+    /// Market cancelled event
+    MarketCancelled {
+        market_id: MarketId,
+        cancelled_by: Owner,
+    },
+    /// Creator bond shared between the market's bettors in proportion to their stake
+    CreatorBondSlashed {
+        market_id: MarketId,
+        amount: Amount,
+    },
+    /// Market expired without resolution; every stake can be refunded
+    MarketExpired {
+        market_id: MarketId,
+    },
+    /// Refund claimed event
+    RefundClaimed {
+        market_id: MarketId,
+        user: Owner,
+        amount: Amount,
+    },
+    /// Market announced to the registry chain on creation
+    RegistryMarketCreated {
+        summary: MarketSummary,
+    },
+    /// Market status change announced to the registry chain
+    RegistryMarketUpdated {
+        market_id: MarketId,
+        status: MarketStatus,
+    },
+}
@@ -0,0 +1,85 @@
+//! Globally unique market identifiers
+//!
+//! A market is identified by the chain that created it and the value of that chain's
+//! `next_market_id` counter at creation time. Markets never move, so the chain in the
+//! ID is also the chain hosting the market.
+//!
+//! The canonical string form is `<chain id>:<index>`, e.g. `e476…a65:3`. It contains no
+//! characters that need escaping in URLs or GraphQL string arguments, and is also how
+//! the ID is written in human-readable formats such as the JSON snapshot.
+
+use crate::PredictionMarketError;
+use linera_sdk::base::ChainId;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Identifier of a market, unique across every chain running the application
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MarketId {
+    /// Chain that created, and hosts, the market
+    pub chain_id: ChainId,
+    /// Value of the chain's market counter when the market was created
+    pub index: u64,
+}
+
+impl MarketId {
+    /// Build the ID of the `index`-th market created on `chain_id`
+    pub fn new(chain_id: ChainId, index: u64) -> Self {
+        MarketId { chain_id, index }
+    }
+}
+
+impl fmt::Display for MarketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.chain_id, self.index)
+    }
+}
+
+impl FromStr for MarketId {
+    type Err = PredictionMarketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PredictionMarketError::InvalidMarketId(s.to_string());
+        let (chain_id, index) = s.split_once(':').ok_or_else(invalid)?;
+        Ok(MarketId {
+            chain_id: chain_id.parse().map_err(|_| invalid())?,
+            index: index.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Human-readable formats (JSON) use the canonical string, so IDs can be map keys;
+/// binary formats (storage keys, cross-chain messages) keep the compact pair.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "MarketId")]
+struct MarketIdParts {
+    chain_id: ChainId,
+    index: u64,
+}
+
+impl Serialize for MarketId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            MarketIdParts {
+                chain_id: self.chain_id,
+                index: self.index,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MarketId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            s.parse().map_err(serde::de::Error::custom)
+        } else {
+            let parts = MarketIdParts::deserialize(deserializer)?;
+            Ok(MarketId::new(parts.chain_id, parts.index))
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Parimutuel payout calculation
+//!
+//! Pure functions computing how a market pool is split between winning bets. They do not
+//! touch the state so `resolve_market` can load the winners, compute, and write back.
+//!
+//! All amounts are computed in attos (`u128`) with checked arithmetic; any overflow fails
+//! the operation with `PredictionMarketError::ArithmeticOverflow`. Proportional shares
+//! multiply through a 256-bit intermediate, so they are exact whenever the result itself
+//! fits in an `Amount`.
+//!
+//! Protocol and creator fees are taken off the pool first; rewards are computed on what is
+//! left. Rounding rule: every fee and reward is rounded down to the nearest atto. The atto
+//! remainders left by rounding are reported as `dust`, so that
+//! `sum(rewards) + surplus + dust + fees == total_pool` always holds.
+
+use crate::PredictionMarketError;
+use linera_sdk::base::Amount;
+
+/// Rewards for each winning bet and the parts of the pool nobody received.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Distribution {
+    /// Reward of each winning bet, in the order of the stakes passed in
+    pub rewards: Vec<Amount>,
+    /// Pool amount withheld by the `max_reward` cap
+    pub surplus: Amount,
+    /// Pool amount lost to rounding rewards down
+    pub dust: Amount,
+}
+
+/// Add two amounts, failing on overflow
+pub fn checked_add(a: Amount, b: Amount) -> Result<Amount, PredictionMarketError> {
+    u128::from(a)
+        .checked_add(u128::from(b))
+        .map(Amount::from_attos)
+        .ok_or(PredictionMarketError::ArithmeticOverflow)
+}
+
+/// Subtract `b` from `a`, failing on underflow
+pub fn checked_sub(a: Amount, b: Amount) -> Result<Amount, PredictionMarketError> {
+    u128::from(a)
+        .checked_sub(u128::from(b))
+        .map(Amount::from_attos)
+        .ok_or(PredictionMarketError::ArithmeticOverflow)
+}
+
+/// Basis points in 100%
+pub const MAX_BPS: u16 = 10_000;
+
+/// Compute a fee of `bps` basis points on `amount`, rounded down
+pub fn fee(amount: Amount, bps: u16) -> Result<Amount, PredictionMarketError> {
+    mul_div(u128::from(amount), u128::from(bps), u128::from(MAX_BPS)).map(Amount::from_attos)
+}
+
+/// Share of `amount` owed to `weight` out of `total_weight`, rounded down; zero if the
+/// total weight is zero
+pub fn share(amount: Amount, weight: Amount, total_weight: Amount) -> Result<Amount, PredictionMarketError> {
+    if total_weight == Amount::ZERO {
+        return Ok(Amount::ZERO);
+    }
+    mul_div(u128::from(weight), u128::from(amount), u128::from(total_weight)).map(Amount::from_attos)
+}
+
+/// Split `amount` between `weights` in proportion to their size, rounded down.
+///
+/// Returns each share, in the order of the weights, and the rounding dust. If every weight
+/// is zero the whole amount is dust.
+pub fn pro_rata(weights: &[Amount], amount: Amount) -> Result<(Vec<Amount>, Amount), PredictionMarketError> {
+    let amount = u128::from(amount);
+    let total = weights
+        .iter()
+        .try_fold(0u128, |sum, weight| sum.checked_add(u128::from(*weight)))
+        .ok_or(PredictionMarketError::ArithmeticOverflow)?;
+    if total == 0 {
+        return Ok((vec![Amount::ZERO; weights.len()], Amount::from_attos(amount)));
+    }
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut paid: u128 = 0;
+    for weight in weights {
+        let share = mul_div(u128::from(*weight), amount, total)?;
+        paid += share;
+        shares.push(Amount::from_attos(share));
+    }
+    let dust = amount.checked_sub(paid).ok_or(PredictionMarketError::PayoutExceedsPool)?;
+    Ok((shares, Amount::from_attos(dust)))
+}
+
+/// Compute `value * numerator / denominator`, rounded down.
+///
+/// The product is kept in 256 bits, so this only fails if `denominator` is zero or the
+/// quotient does not fit in a `u128`.
+fn mul_div(value: u128, numerator: u128, denominator: u128) -> Result<u128, PredictionMarketError> {
+    if denominator == 0 {
+        return Err(PredictionMarketError::ArithmeticOverflow);
+    }
+    if let Some(product) = value.checked_mul(numerator) {
+        return Ok(product / denominator);
+    }
+    let (high, low) = widening_mul(value, numerator);
+    if high >= denominator {
+        return Err(PredictionMarketError::ArithmeticOverflow);
+    }
+
+    // Long division of `high:low` by `denominator`, one bit of `low` at a time. The
+    // remainder stays below `denominator`, so shifting it loses at most the carry bit.
+    let mut remainder = high;
+    let mut quotient: u128 = 0;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1 << bit;
+        }
+    }
+    Ok(quotient)
+}
+
+/// Full 256-bit product of `a` and `b`, as its high and low halves
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const LOW_MASK: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW_MASK);
+    let (b_high, b_low) = (b >> 64, b & LOW_MASK);
+
+    let low_low = a_low * b_low;
+    let low_high = a_low * b_high;
+    let high_low = a_high * b_low;
+    let high_high = a_high * b_high;
+
+    // Middle 64-bit column with its carries; at most three 64-bit terms, so no overflow
+    let middle = (low_low >> 64) + (low_high & LOW_MASK) + (high_low & LOW_MASK);
+    let low = (low_low & LOW_MASK) | (middle << 64);
+    let high = high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// Split `total_pool` between winning `stakes` in proportion to their size, capping each
+/// reward at `max_reward`.
+///
+/// With `redistribute` set, the amount withheld by the cap is shared again pro rata between
+/// the winners still below the cap, until either nothing is left or every winner is capped.
+/// Whatever cannot be redistributed because every winner is capped is returned as `surplus`.
+pub fn winner_rewards(
+    stakes: &[Amount],
+    total_pool: Amount,
+    winning_pool: Amount,
+    max_reward: Amount,
+    redistribute: bool,
+) -> Result<Distribution, PredictionMarketError> {
+    let stakes: Vec<u128> = stakes.iter().map(|stake| u128::from(*stake)).collect();
+    let total_pool = u128::from(total_pool);
+    let winning_pool = u128::from(winning_pool);
+    let max_reward = u128::from(max_reward);
+    if winning_pool == 0 {
+        return Ok(Distribution {
+            rewards: vec![Amount::ZERO; stakes.len()],
+            surplus: Amount::from_attos(total_pool),
+            dust: Amount::ZERO,
+        });
+    }
+
+    // Calculate reward: (bet_amount * total_pool) / winning_pool, capped at max_reward
+    let mut rewards = Vec::with_capacity(stakes.len());
+    let mut surplus: u128 = 0;
+    for stake in &stakes {
+        let reward = mul_div(*stake, total_pool, winning_pool)?;
+        if reward > max_reward {
+            surplus += reward - max_reward;
+            rewards.push(max_reward);
+        } else {
+            rewards.push(reward);
+        }
+    }
+
+    // Share the capped amount between the winners still below the cap
+    while redistribute && surplus > 0 {
+        let uncapped: Vec<usize> = (0..stakes.len()).filter(|&i| rewards[i] < max_reward).collect();
+        let uncapped_stake = uncapped
+            .iter()
+            .try_fold(0u128, |sum, &i| sum.checked_add(stakes[i]))
+            .ok_or(PredictionMarketError::ArithmeticOverflow)?;
+        if uncapped_stake == 0 {
+            break;
+        }
+
+        let pool = surplus;
+        let mut given: u128 = 0;
+        let mut newly_capped = false;
+        for i in uncapped {
+            let room = max_reward - rewards[i];
+            let mut extra = mul_div(stakes[i], pool, uncapped_stake)?;
+            if extra >= room {
+                extra = room;
+                newly_capped = true;
+            }
+            rewards[i] += extra;
+            given += extra;
+        }
+        surplus = pool - given;
+
+        // Without a newly capped winner, what is left is rounding dust
+        if !newly_capped {
+            surplus = 0;
+        }
+    }
+
+    // The sum of all rewards never exceeds the pool; the rest is surplus and rounding dust
+    let paid = rewards
+        .iter()
+        .try_fold(0u128, |sum, reward| sum.checked_add(*reward))
+        .ok_or(PredictionMarketError::ArithmeticOverflow)?;
+    let dust = total_pool
+        .checked_sub(paid)
+        .and_then(|rest| rest.checked_sub(surplus))
+        .ok_or(PredictionMarketError::PayoutExceedsPool)?;
+
+    Ok(Distribution {
+        rewards: rewards.into_iter().map(Amount::from_attos).collect(),
+        surplus: Amount::from_attos(surplus),
+        dust: Amount::from_attos(dust),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(count: u128) -> Amount {
+        Amount::from_tokens(count)
+    }
+
+    fn attos(count: u128) -> Amount {
+        Amount::from_attos(count)
+    }
+
+    /// Check that a distribution accounts for every atto of `total_pool`
+    fn assert_conserved(distribution: &Distribution, total_pool: Amount) {
+        let paid = distribution
+            .rewards
+            .iter()
+            .try_fold(Amount::ZERO, |sum, reward| checked_add(sum, *reward))
+            .unwrap();
+        let accounted = checked_add(checked_add(paid, distribution.surplus).unwrap(), distribution.dust).unwrap();
+        assert_eq!(accounted, total_pool);
+    }
+
+    #[test]
+    fn mul_div_is_exact_beyond_u128_products() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Ok(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 3, 6), Ok(u128::MAX / 2));
+        assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 90), Ok(1 << 110));
+        assert_eq!(mul_div(u128::MAX, 2, 1), Err(PredictionMarketError::ArithmeticOverflow));
+        assert_eq!(mul_div(1, 1, 0), Err(PredictionMarketError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn fee_on_large_amounts() {
+        let amount = attos(u128::MAX);
+        assert_eq!(fee(amount, MAX_BPS), Ok(amount));
+        assert_eq!(fee(tokens(1_000_000_000), 250), Ok(tokens(25_000_000)));
+    }
+
+    #[test]
+    fn winner_rewards_on_large_pools() {
+        // stake * pool is far beyond u128 in attos
+        let stakes = [tokens(3_000_000), tokens(1_000_000)];
+        let total_pool = tokens(10_000_000);
+        let distribution = winner_rewards(&stakes, total_pool, tokens(4_000_000), attos(u128::MAX), false).unwrap();
+        assert_eq!(distribution.rewards, vec![tokens(7_500_000), tokens(2_500_000)]);
+        assert_eq!(distribution.surplus, Amount::ZERO);
+        assert_eq!(distribution.dust, Amount::ZERO);
+        assert_conserved(&distribution, total_pool);
+    }
+
+    #[test]
+    fn winner_rewards_cap_without_redistribution() {
+        let stakes = [tokens(1), tokens(3)];
+        let total_pool = tokens(8);
+        let distribution = winner_rewards(&stakes, total_pool, tokens(4), tokens(4), false).unwrap();
+        assert_eq!(distribution.rewards, vec![tokens(2), tokens(4)]);
+        assert_eq!(distribution.surplus, tokens(2));
+        assert_eq!(distribution.dust, Amount::ZERO);
+        assert_conserved(&distribution, total_pool);
+    }
+
+    #[test]
+    fn winner_rewards_cap_with_redistribution() {
+        let stakes = [tokens(1), tokens(3)];
+        let total_pool = tokens(8);
+        let distribution = winner_rewards(&stakes, total_pool, tokens(4), tokens(4), true).unwrap();
+        assert_eq!(distribution.rewards, vec![tokens(4), tokens(4)]);
+        assert_eq!(distribution.surplus, Amount::ZERO);
+        assert_eq!(distribution.dust, Amount::ZERO);
+        assert_conserved(&distribution, total_pool);
+    }
+
+    #[test]
+    fn winner_rewards_keep_surplus_once_everyone_is_capped() {
+        let stakes = [tokens(1), tokens(1)];
+        let total_pool = tokens(10);
+        let distribution = winner_rewards(&stakes, total_pool, tokens(2), tokens(3), true).unwrap();
+        assert_eq!(distribution.rewards, vec![tokens(3), tokens(3)]);
+        assert_eq!(distribution.surplus, tokens(4));
+        assert_conserved(&distribution, total_pool);
+    }
+
+    #[test]
+    fn winner_rewards_report_rounding_dust() {
+        let stakes = [attos(1), attos(1), attos(1)];
+        let total_pool = attos(10);
+        let distribution = winner_rewards(&stakes, total_pool, attos(3), tokens(1), true).unwrap();
+        assert_eq!(distribution.rewards, vec![attos(3); 3]);
+        assert_eq!(distribution.surplus, Amount::ZERO);
+        assert_eq!(distribution.dust, attos(1));
+        assert_conserved(&distribution, total_pool);
+    }
+
+    #[test]
+    fn winner_rewards_conserve_the_pool_after_fees() {
+        let total_pool = attos(1_000_000_000_000_000_000_007);
+        let protocol_fee = fee(total_pool, 100).unwrap();
+        let creator_fee = fee(total_pool, 250).unwrap();
+        let distributable = checked_sub(total_pool, checked_add(protocol_fee, creator_fee).unwrap()).unwrap();
+
+        let stakes = [tokens(7), attos(13), tokens(500)];
+        let winning_pool = stakes.iter().try_fold(Amount::ZERO, |sum, stake| checked_add(sum, *stake)).unwrap();
+        let distribution = winner_rewards(&stakes, distributable, winning_pool, tokens(400), true).unwrap();
+        assert_conserved(&distribution, distributable);
+        assert!(distribution.rewards.iter().all(|reward| *reward <= tokens(400)));
+    }
+
+    #[test]
+    fn pro_rata_on_large_amounts() {
+        let weights = [tokens(2_000_000), tokens(1_000_000)];
+        let (shares, dust) = pro_rata(&weights, tokens(3_000_000)).unwrap();
+        assert_eq!(shares, vec![tokens(2_000_000), tokens(1_000_000)]);
+        assert_eq!(dust, Amount::ZERO);
+    }
+
+    #[test]
+    fn pro_rata_reports_rounding_dust() {
+        let (shares, dust) = pro_rata(&[attos(1), attos(1), attos(1)], attos(10)).unwrap();
+        assert_eq!(shares, vec![attos(3); 3]);
+        assert_eq!(dust, attos(1));
+    }
+
+    #[test]
+    fn pro_rata_without_weights_is_all_dust() {
+        let (shares, dust) = pro_rata(&[Amount::ZERO, Amount::ZERO], tokens(5)).unwrap();
+        assert_eq!(shares, vec![Amount::ZERO; 2]);
+        assert_eq!(dust, tokens(5));
+    }
+}
@@ -0,0 +1,505 @@
+//! Data types stored by the contract and returned in query snapshots
+
+use crate::{payout, MarketId, PredictionMarketError, PredictionMarketParameters};
+use linera_sdk::base::{Amount, ApplicationId, ChainId, CryptoHash, Owner, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A prediction market.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Market {
+    /// Unique market ID
+    pub id: MarketId,
+    /// Creator of the market
+    pub creator: Owner,
+    /// Market question
+    pub question: String,
+    /// Market description
+    pub description: String,
+    /// How the correct answer will be determined
+    pub resolution_criteria: String,
+    /// Where the resolver will look for the answer (publication, URL, data feed)
+    pub resolution_source: String,
+    /// Evidence backing the resolved or proposed answer; the arbiter's once a dispute is ruled on
+    pub resolution_evidence: Option<Evidence>,
+    /// End time (timestamp in milliseconds)
+    pub end_time: Timestamp,
+    /// Minutes before `end_time` at which betting closes (`None` closes at `end_time`)
+    pub lock_before_end: Option<u64>,
+    /// Market status
+    pub status: MarketStatus,
+    /// Available options
+    pub options: Vec<String>,
+    /// Correct answer (set when resolved)
+    pub correct_answer: Option<String>,
+    /// Total bets per option
+    pub bets: BTreeMap<String, Amount>,
+    /// Total pool amount
+    pub total_pool: Amount,
+    /// Maximum reward per winner (in native tokens)
+    pub max_reward: Amount,
+    /// What happens to the pool if nobody bet on the correct answer
+    pub no_winner_policy: NoWinnerPolicy,
+    /// What happens to the part of the pool withheld by the `max_reward` cap
+    pub surplus_policy: SurplusPolicy,
+    /// Pool amount not paid to winners because of the `max_reward` cap (set on resolution)
+    pub surplus: Amount,
+    /// Pool amount left in escrow by rounding rewards down, or withheld by the cap without a
+    /// treasury to receive it (set on resolution), plus any part of a slashed creator bond
+    /// that could not be shared between bettors
+    pub dust: Amount,
+    /// Bond escrowed from the creator at creation; zero once returned or slashed
+    pub creator_bond: Amount,
+    /// Whether an admin paused betting on this market
+    pub betting_paused: bool,
+    /// Fee in basis points kept in the pool when a bet is withdrawn
+    pub withdrawal_fee_bps: u16,
+    /// Withdrawal fees kept in the pool; winners share them with the rest of the pool, and
+    /// refunds share them in proportion to the refunded stake
+    pub withdrawal_fees: Amount,
+    /// Protocol fee in basis points taken off the pool at resolution
+    pub protocol_fee_bps: u16,
+    /// Creator fee in basis points taken off the pool at resolution
+    pub creator_fee_bps: u16,
+    /// Protocol fee accrued to the treasury (set on resolution)
+    pub protocol_fee: Amount,
+    /// Creator fee accrued to the creator (set on resolution)
+    pub creator_fee: Amount,
+    /// Who decides the correct answer
+    pub resolver: Resolver,
+    /// Latest answer submitted by each member of a resolver committee
+    pub resolution_votes: BTreeMap<Owner, String>,
+    /// Challenge window and bonds; without them the resolver's answer is final immediately
+    pub dispute_settings: Option<DisputeSettings>,
+    /// Answer proposed by the resolver, while it can be disputed or is being arbitrated
+    pub proposal: Option<Proposal>,
+    /// Stake-weighted bettor votes per option, for markets resolved by vote
+    pub vote_tally: BTreeMap<String, Amount>,
+    /// Time after which an unresolved market can be expired and refunded (`None` never expires)
+    pub resolution_deadline: Option<Timestamp>,
+    /// Creation timestamp
+    pub created_at: Timestamp,
+}
+
+impl Market {
+    /// Time from which the market no longer accepts bets
+    pub fn lock_time(&self) -> Timestamp {
+        let window = self.lock_before_end.unwrap_or(0).saturating_mul(60 * 1000); // Convert minutes to milliseconds
+        Timestamp::from(self.end_time.saturating_sub(Timestamp::from(window)))
+    }
+
+    /// End of the bettor voting window, for markets resolved by vote
+    pub fn voting_ends(&self) -> Option<Timestamp> {
+        match self.resolver {
+            Resolver::BettorVote { voting_minutes } => {
+                let window = voting_minutes.saturating_mul(60 * 1000); // Convert minutes to milliseconds
+                let end = self.end_time.saturating_sub(Timestamp::from(0)).saturating_add(window);
+                Some(Timestamp::from(end))
+            }
+            _ => None,
+        }
+    }
+
+    /// Part of the protocol and creator fees charged to a winning stake of `stake` on the
+    /// correct answer, in proportion to the winning pool
+    pub fn fee_shares(&self, stake: Amount) -> Result<(Amount, Amount), PredictionMarketError> {
+        let winning_pool = self
+            .correct_answer
+            .as_ref()
+            .and_then(|answer| self.bets.get(answer))
+            .copied()
+            .unwrap_or(Amount::ZERO);
+        Ok((
+            payout::share(self.protocol_fee, stake, winning_pool)?,
+            payout::share(self.creator_fee, stake, winning_pool)?,
+        ))
+    }
+
+    /// Stake still held by bettors: the pool without the withdrawal fees kept in it
+    pub fn staked_pool(&self) -> Result<Amount, PredictionMarketError> {
+        payout::checked_sub(self.total_pool, self.withdrawal_fees)
+    }
+
+    /// Refund owed to a stake of `stake`, with its share of the withdrawal fees
+    pub fn refund_of(&self, stake: Amount) -> Result<Amount, PredictionMarketError> {
+        let fees = payout::share(self.withdrawal_fees, stake, self.staked_pool()?)?;
+        payout::checked_add(stake, fees)
+    }
+
+    /// Count the withdrawal fees of a refunded market as dust if no stake is left to share
+    /// them, because every bet was withdrawn
+    pub fn keep_unshared_withdrawal_fees(&mut self) -> Result<(), PredictionMarketError> {
+        if self.staked_pool()? == Amount::ZERO {
+            self.dust = payout::checked_add(self.dust, self.withdrawal_fees)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the market has been resolved, with or without winners
+    pub fn is_resolved(&self) -> bool {
+        matches!(self.status, MarketStatus::Resolved | MarketStatus::ResolvedNoWinners)
+    }
+
+    /// Move an active market to `Locked` once its lock time has passed.
+    ///
+    /// Returns `true` if the status changed.
+    pub fn refresh_status(&mut self, now: Timestamp) -> bool {
+        if self.status == MarketStatus::Active && now >= self.lock_time() {
+            self.status = MarketStatus::Locked;
+            return true;
+        }
+        false
+    }
+}
+
+/// Market status
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum MarketStatus {
+    /// Market is active and accepting bets
+    Active,
+    /// Market has ended and is locked (no more bets)
+    Locked,
+    /// An answer has been proposed and can be disputed until the challenge window closes
+    Proposed,
+    /// The proposed answer has been disputed and awaits the arbiter's ruling
+    Disputed,
+    /// Market has been resolved
+    Resolved,
+    /// Market has been resolved but nobody bet on the correct answer
+    ResolvedNoWinners,
+    /// Market has been cancelled and every stake can be refunded
+    Cancelled,
+    /// Market was not resolved before its resolution deadline and every stake can be refunded
+    Expired,
+}
+
+/// Policy applied to the pool when nobody bet on the correct answer
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum NoWinnerPolicy {
+    /// Every bettor can reclaim their stake through `ClaimRefund`
+    #[default]
+    RefundBettors,
+    /// The whole pool is sent to the application treasury (the `treasury` parameter) at
+    /// resolution, so nobody involved in the market can sweep it
+    SendToTreasury,
+}
+
+/// Policy applied to the part of the pool withheld by the `max_reward` cap
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum SurplusPolicy {
+    /// Shared pro rata between winners below the cap; any rest goes to the application
+    /// treasury, or stays in escrow as dust without one
+    #[default]
+    RedistributeToWinners,
+    /// Returned to the market creator
+    ReturnToCreator,
+    /// Sent to the application treasury (the `treasury` parameter)
+    SendToTreasury,
+}
+
+/// Who may resolve a market
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum Resolver {
+    /// The market creator
+    #[default]
+    Creator,
+    /// A specific owner
+    Owner { owner: Owner },
+    /// A committee of owners who each submit an answer; the market resolves as soon as
+    /// `quorum` members agree. Members may change their answer until then.
+    Committee { members: Vec<Owner>, quorum: u32 },
+    /// Another application, which resolves by calling `ResolveMarket` on this application.
+    /// The call is authenticated by the caller's application ID.
+    Oracle { application_id: ApplicationId },
+    /// Bettors vote during `voting_minutes` after the end time, weighted by their stake.
+    /// The plurality option wins; a tie, or no vote at all, refunds every bettor.
+    BettorVote { voting_minutes: u64 },
+    /// A price-feed application on this chain, read by `ResolveFromFeed`. The first option
+    /// wins if `condition` holds for the observed price, the second otherwise.
+    PriceFeed {
+        application_id: ApplicationId,
+        condition: PriceCondition,
+    },
+}
+
+/// Condition on a feed price deciding a price-feed market
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct PriceCondition {
+    /// Feed to read on the price-feed application
+    pub feed_id: String,
+    /// How the observed price is compared to `threshold`
+    pub comparator: Comparator,
+    /// Threshold, in the feed's fixed-point units
+    pub threshold: u128,
+    /// The first price published at or after this time is the observed price
+    pub observation_time: Timestamp,
+}
+
+/// Comparison between an observed price and a threshold
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Comparator {
+    /// Price strictly above the threshold
+    Above,
+    /// Price above or equal to the threshold
+    AtOrAbove,
+    /// Price strictly below the threshold
+    Below,
+    /// Price below or equal to the threshold
+    AtOrBelow,
+}
+
+impl Comparator {
+    /// Whether `price` satisfies the comparison with `threshold`
+    pub fn holds(self, price: u128, threshold: u128) -> bool {
+        match self {
+            Comparator::Above => price > threshold,
+            Comparator::AtOrAbove => price >= threshold,
+            Comparator::Below => price < threshold,
+            Comparator::AtOrBelow => price <= threshold,
+        }
+    }
+}
+
+impl Resolver {
+    /// Check that a committee can ever reach its quorum and a vote has time to happen
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Resolver::Committee { members, quorum } => {
+                let mut distinct = members.clone();
+                distinct.sort();
+                distinct.dedup();
+                distinct.len() == members.len() && *quorum >= 1 && *quorum as usize <= members.len()
+            }
+            Resolver::BettorVote { voting_minutes } => *voting_minutes > 0,
+            _ => true,
+        }
+    }
+}
+
+/// A bettor's vote on the correct answer of a market resolved by vote
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Vote {
+    /// Market ID
+    pub market_id: MarketId,
+    /// Bettor who voted
+    pub voter: Owner,
+    /// Option voted for
+    pub option: String,
+    /// Weight of the vote: the voter's total stake on the market
+    pub weight: Amount,
+    /// Timestamp of the (latest) vote
+    pub timestamp: Timestamp,
+}
+
+/// Evidence submitted with an answer
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Evidence {
+    /// Evidence text or URL
+    pub source: String,
+    /// Hash of the evidence content, so the document referenced can be checked later
+    pub content_hash: CryptoHash,
+}
+
+/// Optimistic resolution settings of a market
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DisputeSettings {
+    /// Minutes during which a proposed answer can be disputed
+    pub challenge_minutes: u64,
+    /// Bond posted by the proposer, and matched by the disputer
+    pub bond: Amount,
+    /// Owner ruling on disputed answers
+    pub arbiter: Owner,
+}
+
+/// An answer proposed by the resolver and not yet final
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Proposal {
+    /// Proposed correct answer
+    pub answer: String,
+    /// Owner who posted the proposal bond
+    pub proposer: Owner,
+    /// Timestamp when the answer was proposed
+    pub proposed_at: Timestamp,
+    /// Time until which the answer can be disputed
+    pub challenge_ends: Timestamp,
+    /// Owner who disputed the answer and posted the matching bond
+    pub disputer: Option<Owner>,
+}
+
+/// A single bet placed by a user
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Bet {
+    /// Market ID
+    pub market_id: MarketId,
+    /// User who placed the bet
+    pub user: Owner,
+    /// Selected option
+    pub option: String,
+    /// Bet amount
+    pub amount: Amount,
+    /// Timestamp when bet was placed
+    pub timestamp: Timestamp,
+}
+
+/// Stake pulled out of a position before the market locked
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Withdrawal {
+    /// Option the stake was withdrawn from
+    pub option: String,
+    /// Stake removed from the position
+    pub amount: Amount,
+    /// Part of `amount` kept in the pool as withdrawal fee
+    pub fee: Amount,
+    /// Timestamp when the stake was withdrawn
+    pub timestamp: Timestamp,
+}
+
+/// Registry entry describing a market hosted on some chain
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MarketSummary {
+    /// Market ID, including the chain hosting the market
+    pub market_id: MarketId,
+    /// Market question
+    pub question: String,
+    /// Last announced market status
+    pub status: MarketStatus,
+    /// End time (timestamp in milliseconds)
+    pub end_time: Timestamp,
+}
+
+/// A page of the global market index
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct RegistryPage {
+    /// Entries of the page, in the registry's key order, which is stable between pages
+    pub items: Vec<MarketSummary>,
+    /// Number of entries in the whole index
+    pub total: u64,
+}
+
+/// A bet sent to a market hosted on another chain, not yet acknowledged
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct OutgoingBet {
+    /// Market ID, including the chain hosting the market
+    pub market_id: MarketId,
+    /// User who placed the bet
+    pub user: Owner,
+    /// Selected option
+    pub option: String,
+    /// Bet amount, already sent to the market chain
+    pub amount: Amount,
+    /// Timestamp when the bet was sent
+    pub timestamp: Timestamp,
+}
+
+/// Everything a user has staked on one market
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Position {
+    /// Market ID
+    pub market_id: MarketId,
+    /// User holding the position
+    pub user: Owner,
+    /// Chain the bets were placed from; withdrawals, rewards and refunds are paid to the
+    /// user's account there, and remote requests are only accepted from it
+    pub origin: ChainId,
+    /// Total stake per option
+    pub stakes: BTreeMap<String, Amount>,
+    /// Every bet making up the position, in the order they were placed (append-only)
+    pub bets: Vec<Bet>,
+    /// Every withdrawal from the position, in the order they were made (append-only)
+    pub withdrawals: Vec<Withdrawal>,
+    /// Whether the reward or refund has been paid out
+    pub claimed: bool,
+    /// Reward amount (calculated on resolution)
+    pub reward_amount: Amount,
+}
+
+impl Position {
+    /// Create an empty position for bets placed from `origin`
+    pub fn new(market_id: MarketId, user: Owner, origin: ChainId) -> Self {
+        Position {
+            market_id,
+            user,
+            origin,
+            stakes: BTreeMap::new(),
+            bets: Vec::new(),
+            withdrawals: Vec::new(),
+            claimed: false,
+            reward_amount: Amount::ZERO,
+        }
+    }
+
+    /// Stake held on `option`
+    pub fn stake_on(&self, option: &str) -> Amount {
+        self.stakes.get(option).copied().unwrap_or(Amount::ZERO)
+    }
+
+    /// Total stake across all options
+    pub fn total_stake(&self) -> Amount {
+        self.stakes.values().fold(Amount::ZERO, |sum, stake| sum.saturating_add(*stake))
+    }
+
+    /// What the user can still claim from `market`, if anything
+    pub fn claimable(&self, market: &Market) -> Result<Option<Claimable>, PredictionMarketError> {
+        if self.claimed {
+            return Ok(None);
+        }
+        let refundable = match market.status {
+            MarketStatus::Cancelled | MarketStatus::Expired => true,
+            MarketStatus::ResolvedNoWinners => market.no_winner_policy == NoWinnerPolicy::RefundBettors,
+            MarketStatus::Resolved if self.reward_amount > Amount::ZERO => {
+                return Ok(Some(Claimable::Reward(self.reward_amount)));
+            }
+            _ => false,
+        };
+        // A position whose whole stake was withdrawn has nothing to refund
+        let stake = self.total_stake();
+        if !refundable || stake == Amount::ZERO {
+            return Ok(None);
+        }
+        Ok(Some(Claimable::Refund(market.refund_of(stake)?)))
+    }
+}
+
+/// An outstanding payout for a position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Claimable {
+    /// Winnings from a resolved market, paid through `ClaimReward`
+    Reward(Amount),
+    /// Returned stake, paid through `ClaimRefund`
+    Refund(Amount),
+}
+
+/// Fees accrued to one recipient
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct FeeBalance {
+    pub owner: Owner,
+    pub amount: Amount,
+}
+
+/// A serializable copy of the whole state, returned by the service for GraphQL queries.
+///
+/// Positions are flattened into a list because JSON maps cannot have tuple keys; market
+/// IDs are written in their canonical string form.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct PredictionMarketSnapshot {
+    /// Index of the next market created on this chain
+    pub next_market_id: u64,
+    /// Markets hosted on this chain indexed by ID
+    pub markets: BTreeMap<MarketId, Market>,
+    /// All user positions
+    pub positions: Vec<Position>,
+    /// Bets sent to other chains and not yet acknowledged
+    pub outgoing_bets: Vec<OutgoingBet>,
+    /// All bettor votes
+    pub votes: Vec<Vote>,
+    /// Accrued fees not yet withdrawn, per recipient
+    pub fee_balances: Vec<FeeBalance>,
+    /// Admins of this chain
+    pub admins: Vec<Owner>,
+    /// Whether market creation is paused
+    pub creation_paused: bool,
+    /// Whether betting is paused on every market
+    pub betting_paused: bool,
+    /// Application parameters, filled in by `handle_query`
+    pub parameters: PredictionMarketParameters,
+}
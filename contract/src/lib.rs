@@ -1,172 +1,75 @@
 //! Prediction Market Contract for Linera
-//! 
+//!
 //! This contract implements a real-time prediction market system where users can:
 //! - Create prediction markets with multiple options
 //! - Place bets on market options
 //! - Resolve markets and claim rewards
 //! - Track market state in real-time
 
-use linera_sdk::base::{Amount, ApplicationId, ChainId, Owner, Timestamp};
-use linera_sdk::views::{MapView, View, ViewStorageContext};
+use linera_sdk::base::{Account, AccountOwner, Amount, ChainId, Owner, Timestamp};
+use linera_sdk::views::{RootView, View};
 use linera_sdk::{Contract, Service};
-use serde::{Deserialize, Serialize};
+use prediction_market_abi::payout;
+use price_feed::{PriceFeedCall, PriceObservation};
 use std::collections::BTreeMap;
 
+mod migration;
+mod state;
+
+pub use migration::STATE_VERSION;
+pub use prediction_market_abi::{
+    Bet, Claimable, Comparator, DisputeSettings, Evidence, FeeBalance, Market, MarketId, MarketStatus,
+    MarketSummary, NoWinnerPolicy, OutgoingBet, Position, PredictionMarketAbi, PredictionMarketCall,
+    PredictionMarketEffect, PredictionMarketError, PredictionMarketInstantiationArgument,
+    PredictionMarketMessage, PredictionMarketParameters, PredictionMarketQuery, PredictionMarketSnapshot,
+    PriceCondition, Proposal, RegistryPage, Resolver, SurplusPolicy, Vote, Withdrawal,
+};
+pub use state::PredictionMarketState;
+
 /// The prediction market application.
-#[derive(Debug)]
 pub struct PredictionMarket {
     state: PredictionMarketState,
-}
-
-/// The state of the prediction market application.
-#[derive(Clone, Serialize, Deserialize)]
-pub struct PredictionMarketState {
-    /// Next market ID (auto-incrementing)
-    pub next_market_id: u64,
-    /// Markets indexed by ID
-    pub markets: BTreeMap<u64, Market>,
-    /// User bets indexed by (market_id, owner)
-    pub user_bets: BTreeMap<(u64, Owner), Bet>,
-}
-
-/// A prediction market.
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct Market {
-    /// Unique market ID
-    pub id: u64,
-    /// Creator of the market
-    pub creator: Owner,
-    /// Market question
-    pub question: String,
-    /// Market description
-    pub description: String,
-    /// End time (timestamp in milliseconds)
-    pub end_time: Timestamp,
-    /// Market status
-    pub status: MarketStatus,
-    /// Available options
-    pub options: Vec<String>,
-    /// Correct answer (set when resolved)
-    pub correct_answer: Option<String>,
-    /// Total bets per option
-    pub bets: BTreeMap<String, Amount>,
-    /// Total pool amount
-    pub total_pool: Amount,
-    /// Maximum reward per winner (in native tokens)
-    pub max_reward: Amount,
-    /// Creation timestamp
-    pub created_at: Timestamp,
-}
-
-/// Market status
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub enum MarketStatus {
-    /// Market is active and accepting bets
-    Active,
-    /// Market has ended and is locked (no more bets)
-    Locked,
-    /// Market has been resolved
-    Resolved,
-}
-
-/// A user bet
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct Bet {
-    /// Market ID
-    pub market_id: u64,
-    /// User who placed the bet
-    pub user: Owner,
-    /// Selected option
-    pub option: String,
-    /// Bet amount
-    pub amount: Amount,
-    /// Timestamp when bet was placed
-    pub timestamp: Timestamp,
-    /// Whether reward has been claimed
-    pub claimed: bool,
-    /// Reward amount (calculated on resolution)
-    pub reward_amount: Amount,
-}
-
-/// Message for the prediction market application.
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub enum PredictionMarketMessage {
-    /// Create a new prediction market
-    CreateMarket {
-        question: String,
-        description: String,
-        duration_minutes: u64,
-        options: Vec<String>,
-        max_reward: Amount,
-    },
-    /// Place a bet on a market option
-    PlaceBet {
-        market_id: u64,
-        option: String,
-        amount: Amount,
-    },
-    /// Resolve a market (creator only)
-    ResolveMarket {
-        market_id: u64,
-        correct_answer: String,
-    },
-    /// Claim reward from a resolved market
-    ClaimReward {
-        market_id: u64,
-    },
-}
-
-/// Effect for the prediction market application.
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub enum PredictionMarketEffect {
-    /// Market created event
-    MarketCreated {
-        market_id: u64,
-        creator: Owner,
-    },
-    /// Bet placed event
-    BetPlaced {
-        market_id: u64,
-        user: Owner,
-        option: String,
-        amount: Amount,
-    },
-    /// Market resolved event
-    MarketResolved {
-        market_id: u64,
-        correct_answer: String,
-    },
-    /// Reward claimed event
-    RewardClaimed {
-        market_id: u64,
-        user: Owner,
-        amount: Amount,
-    },
-}
-
-impl Default for PredictionMarketState {
-    fn default() -> Self {
-        Self {
-            next_market_id: 1,
-            markets: BTreeMap::new(),
-            user_bets: BTreeMap::new(),
-        }
-    }
+    parameters: PredictionMarketParameters,
 }
 
 #[async_trait::async_trait]
 impl Contract for PredictionMarket {
     type Message = PredictionMarketMessage;
-    type Parameters = ();
-    type InstantiationArgument = ();
+    type Parameters = PredictionMarketParameters;
+    type InstantiationArgument = PredictionMarketInstantiationArgument;
+    type ApplicationCall = PredictionMarketCall;
+    type CallResponse = Vec<PredictionMarketEffect>;
 
-    async fn instantiate(
-        _context: &linera_sdk::base::ContractRuntimeContext,
-        _argument: Self::InstantiationArgument,
+    async fn load(
+        context: &linera_sdk::base::ContractRuntimeContext,
     ) -> Result<Self, linera_sdk::base::ContractError> {
-        Ok(PredictionMarket {
-            state: PredictionMarketState::default(),
-        })
+        let mut state = PredictionMarketState::load(context.view_storage_context())
+            .await
+            .map_err(PredictionMarketError::from)?;
+        // Upgrade states written by older versions before anything reads them
+        migration::migrate(&mut state, context.view_storage_context(), context.chain_id()).await?;
+        let parameters = context.application_parameters();
+        Ok(PredictionMarket { state, parameters })
+    }
+
+    async fn instantiate(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        argument: Self::InstantiationArgument,
+    ) -> Result<(), linera_sdk::base::ContractError> {
+        self.parameters.validate()?;
+        self.state.next_market_id.set(1);
+
+        // Without explicit admins, the owner instantiating the application becomes the first one
+        let admins = if argument.admins.is_empty() {
+            context.authenticated_signer().into_iter().collect()
+        } else {
+            argument.admins
+        };
+        for admin in admins {
+            self.state.admins.insert(&admin).map_err(PredictionMarketError::from)?;
+        }
+        Ok(())
     }
 
     async fn execute_message(
@@ -174,88 +77,402 @@ impl Contract for PredictionMarket {
         context: &linera_sdk::base::ContractRuntimeContext,
         message: Self::Message,
     ) -> Result<Vec<PredictionMarketEffect>, linera_sdk::base::ContractError> {
-        match message {
+        let effects = match message {
             PredictionMarketMessage::CreateMarket {
                 question,
                 description,
+                resolution_criteria,
+                resolution_source,
                 duration_minutes,
                 options,
                 max_reward,
-            } => self.create_market(context, question, description, duration_minutes, options, max_reward),
+                lock_before_end,
+                no_winner_policy,
+                surplus_policy,
+                withdrawal_fee_bps,
+                creator_fee_bps,
+                resolver,
+                dispute_settings,
+                resolution_deadline_minutes,
+            } => {
+                self.create_market(
+                    context,
+                    question,
+                    description,
+                    resolution_criteria,
+                    resolution_source,
+                    duration_minutes,
+                    options,
+                    max_reward,
+                    lock_before_end,
+                    no_winner_policy,
+                    surplus_policy,
+                    withdrawal_fee_bps,
+                    creator_fee_bps,
+                    resolver,
+                    dispute_settings,
+                    resolution_deadline_minutes,
+                )
+                .await
+            }
             PredictionMarketMessage::PlaceBet {
                 market_id,
                 option,
                 amount,
-            } => self.place_bet(context, market_id, option, amount),
+            } => self.place_bet(context, market_id, option, amount).await,
+            PredictionMarketMessage::PlaceRemoteBet {
+                market_id,
+                option,
+                amount,
+            } => self.place_remote_bet(context, market_id, option, amount).await,
+            PredictionMarketMessage::WithdrawBet {
+                market_id,
+                option,
+                amount,
+            } => {
+                let user = signer(context)?;
+                self.withdraw_bet(context, market_id, user, option, amount).await
+            }
+            PredictionMarketMessage::WithdrawRemoteBet {
+                market_id,
+                option,
+                amount,
+            } => self.withdraw_remote_bet(context, market_id, option, amount).await,
             PredictionMarketMessage::ResolveMarket {
                 market_id,
                 correct_answer,
-            } => self.resolve_market(context, market_id, correct_answer),
-            PredictionMarketMessage::ClaimReward { market_id } => self.claim_reward(context, market_id),
-        }
+                evidence,
+            } => self.resolve_market(context, market_id, correct_answer, evidence).await,
+            PredictionMarketMessage::DisputeResolution { market_id } => {
+                self.dispute_resolution(context, market_id).await
+            }
+            PredictionMarketMessage::FinalizeResolution { market_id } => {
+                self.finalize_resolution(context, market_id).await
+            }
+            PredictionMarketMessage::ArbitrateMarket {
+                market_id,
+                correct_answer,
+                evidence,
+            } => self.arbitrate_market(context, market_id, correct_answer, evidence).await,
+            PredictionMarketMessage::CastVote { market_id, option } => {
+                let user = signer(context)?;
+                self.cast_vote(context, market_id, user, option).await
+            }
+            PredictionMarketMessage::CastRemoteVote { market_id, option } => {
+                self.cast_remote_vote(context, market_id, option).await
+            }
+            PredictionMarketMessage::CloseVote { market_id } => self.close_vote(context, market_id).await,
+            PredictionMarketMessage::ResolveFromFeed { market_id } => {
+                self.resolve_from_feed(context, market_id).await
+            }
+            PredictionMarketMessage::ExpireMarket { market_id } => self.expire_market(context, market_id).await,
+            PredictionMarketMessage::ClaimReward { market_id } => {
+                let user = signer(context)?;
+                self.claim_reward(context, market_id, user).await
+            }
+            PredictionMarketMessage::ClaimRemoteReward { market_id } => {
+                self.claim_remote_reward(context, market_id).await
+            }
+            PredictionMarketMessage::LockMarket { market_id } => self.lock_market(context, market_id).await,
+            PredictionMarketMessage::CancelMarket { market_id, at_fault } => {
+                self.cancel_market(context, market_id, at_fault).await
+            }
+            PredictionMarketMessage::ClaimRefund { market_id } => {
+                let user = signer(context)?;
+                self.claim_refund(context, market_id, user).await
+            }
+            PredictionMarketMessage::ClaimRemoteRefund { market_id } => {
+                self.claim_remote_refund(context, market_id).await
+            }
+            PredictionMarketMessage::WithdrawFees => self.withdraw_fees(context).await,
+            PredictionMarketMessage::SetCreationPaused { paused } => {
+                self.set_creation_paused(context, paused).await
+            }
+            PredictionMarketMessage::SetBettingPaused { paused } => {
+                self.set_betting_paused(context, paused).await
+            }
+            PredictionMarketMessage::SetMarketBettingPaused { market_id, paused } => {
+                self.set_market_betting_paused(context, market_id, paused).await
+            }
+            PredictionMarketMessage::ForceCancelMarket { market_id, at_fault } => {
+                self.force_cancel_market(context, market_id, at_fault).await
+            }
+            PredictionMarketMessage::AddAdmin { owner } => self.add_admin(context, owner).await,
+            PredictionMarketMessage::RemoveAdmin { owner } => self.remove_admin(context, owner).await,
+        };
+        effects.map_err(Into::into)
     }
 
     async fn execute_effect(
         &mut self,
-        _context: &linera_sdk::base::ContractRuntimeContext,
-        _effect: PredictionMarketEffect,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        effect: PredictionMarketEffect,
     ) -> Result<Vec<PredictionMarketEffect>, linera_sdk::base::ContractError> {
         // Effects are executed on other chains
-        Ok(vec![])
+        let effects = match effect {
+            PredictionMarketEffect::RemoteBet {
+                market_id,
+                user,
+                option,
+                amount,
+                origin,
+            } => {
+                self.receive_remote_bet(context, market_id, user, option, amount, origin)
+                    .await
+            }
+            PredictionMarketEffect::RemoteBetAccepted { market_id, user, .. }
+            | PredictionMarketEffect::RemoteBetRejected { market_id, user, .. } => {
+                self.settle_outgoing_bet(context, market_id, user).await
+            }
+            PredictionMarketEffect::RemoteWithdraw {
+                market_id,
+                user,
+                option,
+                amount,
+            } => {
+                let result = self.withdraw_bet(context, market_id, user, option, amount).await;
+                answer_remote_request(context, market_id, user, result)
+            }
+            PredictionMarketEffect::RemoteVote {
+                market_id,
+                user,
+                option,
+            } => {
+                let result = self.cast_vote(context, market_id, user, option).await;
+                answer_remote_request(context, market_id, user, result)
+            }
+            PredictionMarketEffect::RemoteClaim { market_id, user } => {
+                let result = self.claim_reward(context, market_id, user).await;
+                answer_remote_request(context, market_id, user, result)
+            }
+            PredictionMarketEffect::RemoteRefund { market_id, user } => {
+                let result = self.claim_refund(context, market_id, user).await;
+                answer_remote_request(context, market_id, user, result)
+            }
+            PredictionMarketEffect::RegistryMarketCreated { summary } => {
+                self.register_market(context, summary).await
+            }
+            PredictionMarketEffect::RegistryMarketUpdated { market_id, status } => {
+                self.update_registered_market(context, market_id, status).await
+            }
+            // Every other effect is an event for clients and needs no handling
+            _ => Ok(vec![]),
+        };
+        effects.map_err(Into::into)
+    }
+
+    async fn handle_application_call(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        call: Self::ApplicationCall,
+    ) -> Result<Self::CallResponse, linera_sdk::base::ContractError> {
+        // Oracle applications resolve their markets here, authenticated by their application ID
+        let effects = match call {
+            PredictionMarketCall::ResolveMarket {
+                market_id,
+                correct_answer,
+                evidence,
+            } => self.resolve_market(context, market_id, correct_answer, evidence).await,
+        };
+        effects.map_err(Into::into)
+    }
+
+    async fn store(mut self) -> Result<(), linera_sdk::base::ContractError> {
+        // Only the registers and map entries modified during the block are written back
+        self.state.save().await.map_err(PredictionMarketError::from)?;
+        Ok(())
     }
 }
 
 impl PredictionMarket {
     /// Create a new prediction market
-    fn create_market(
+    #[allow(clippy::too_many_arguments)]
+    async fn create_market(
         &mut self,
         context: &linera_sdk::base::ContractRuntimeContext,
         question: String,
         description: String,
+        resolution_criteria: String,
+        resolution_source: String,
         duration_minutes: u64,
         options: Vec<String>,
         max_reward: Amount,
-    ) -> Result<Vec<PredictionMarketEffect>, linera_sdk::base::ContractError> {
+        lock_before_end: Option<u64>,
+        no_winner_policy: NoWinnerPolicy,
+        surplus_policy: SurplusPolicy,
+        withdrawal_fee_bps: u16,
+        creator_fee_bps: u16,
+        resolver: Resolver,
+        dispute_settings: Option<DisputeSettings>,
+        resolution_deadline_minutes: Option<u64>,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
         // Validate inputs
         if question.is_empty() {
-            return Err(linera_sdk::base::ContractError::Other("Question cannot be empty".to_string()));
+            return Err(PredictionMarketError::EmptyQuestion);
+        }
+        if resolution_criteria.is_empty() {
+            return Err(PredictionMarketError::EmptyResolutionCriteria);
         }
         if options.len() < 2 {
-            return Err(linera_sdk::base::ContractError::Other("At least 2 options required".to_string()));
+            return Err(PredictionMarketError::TooFewOptions);
+        }
+        if self
+            .parameters
+            .max_options
+            .is_some_and(|max_options| options.len() > max_options as usize)
+        {
+            return Err(PredictionMarketError::TooManyOptions);
+        }
+        if self
+            .parameters
+            .max_duration_minutes
+            .is_some_and(|max_minutes| duration_minutes > max_minutes)
+        {
+            return Err(PredictionMarketError::DurationTooLong);
+        }
+        // A cap below the smallest bet would let the surplus policy sweep the pool
+        if max_reward == Amount::ZERO || max_reward < self.parameters.min_bet {
+            return Err(PredictionMarketError::InvalidMaxReward);
+        }
+        if lock_before_end.is_some_and(|minutes| minutes >= duration_minutes) {
+            return Err(PredictionMarketError::InvalidLockWindow);
+        }
+        if withdrawal_fee_bps > payout::MAX_BPS {
+            return Err(PredictionMarketError::InvalidFee);
+        }
+        if creator_fee_bps > self.parameters.max_creator_fee_bps {
+            return Err(PredictionMarketError::CreatorFeeTooHigh);
+        }
+        if (no_winner_policy == NoWinnerPolicy::SendToTreasury || surplus_policy == SurplusPolicy::SendToTreasury)
+            && self.parameters.treasury.is_none()
+        {
+            return Err(PredictionMarketError::NoTreasury);
         }
-        if max_reward == Amount::ZERO {
-            return Err(linera_sdk::base::ContractError::Other("Max reward must be greater than 0".to_string()));
+        if !resolver.is_valid() {
+            return Err(PredictionMarketError::InvalidResolver);
+        }
+        // Bettor votes and price feeds settle the market directly, without a challenge window,
+        // and an oracle application has no signer to post the proposal bond
+        if dispute_settings.as_ref().is_some_and(|settings| {
+            settings.bond == Amount::ZERO
+                || settings.challenge_minutes == 0
+                || matches!(
+                    resolver,
+                    Resolver::BettorVote { .. } | Resolver::PriceFeed { .. } | Resolver::Oracle { .. }
+                )
+        }) {
+            return Err(PredictionMarketError::InvalidDisputeSettings);
+        }
+
+        if *self.state.creation_paused.get() {
+            return Err(PredictionMarketError::CreationPaused);
         }
 
         // Get creator
-        let creator = context.authenticated_signer().ok_or_else(|| {
-            linera_sdk::base::ContractError::Other("Unauthorized: No authenticated signer".to_string())
-        })?;
+        let creator = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+        let creator_bond = self.parameters.creator_bond;
+
+        // Disputes are ruled on by someone who neither created the market nor proposes its answer
+        if let Some(settings) = &dispute_settings {
+            let arbiter_resolves = match &resolver {
+                Resolver::Owner { owner } => *owner == settings.arbiter,
+                Resolver::Committee { members, .. } => members.contains(&settings.arbiter),
+                _ => false,
+            };
+            if settings.arbiter == creator || arbiter_resolves {
+                return Err(PredictionMarketError::InvalidDisputeSettings);
+            }
+        }
 
         // Get current timestamp
         let now = context.system_time();
-        let end_time = now + (duration_minutes * 60 * 1000); // Convert minutes to milliseconds
+        let end_time = add_minutes(now, duration_minutes)?;
+        let resolution_deadline = match resolution_deadline_minutes {
+            Some(0) => return Err(PredictionMarketError::InvalidResolutionDeadline),
+            Some(minutes) => Some(add_minutes(end_time, minutes)?),
+            None => None,
+        };
 
         // Create market
-        let market_id = self.state.next_market_id;
+        let index = self.state.peek_market_id();
+        let market_id = MarketId::new(context.chain_id(), index);
         let market = Market {
             id: market_id,
             creator,
             question: question.clone(),
             description: description.clone(),
+            resolution_criteria,
+            resolution_source,
+            resolution_evidence: None,
             end_time,
+            lock_before_end,
             status: MarketStatus::Active,
             options: options.clone(),
             correct_answer: None,
             bets: BTreeMap::new(),
             total_pool: Amount::ZERO,
             max_reward,
+            no_winner_policy,
+            surplus_policy,
+            surplus: Amount::ZERO,
+            dust: Amount::ZERO,
+            creator_bond,
+            betting_paused: false,
+            withdrawal_fee_bps,
+            withdrawal_fees: Amount::ZERO,
+            protocol_fee_bps: self.parameters.protocol_fee_bps,
+            creator_fee_bps,
+            protocol_fee: Amount::ZERO,
+            creator_fee: Amount::ZERO,
+            resolver,
+            resolution_votes: BTreeMap::new(),
+            dispute_settings,
+            proposal: None,
+            vote_tally: BTreeMap::new(),
+            resolution_deadline,
             created_at: now,
         };
 
+        // Votes and price observations must be able to happen before the deadline
+        if let Some(deadline) = market.resolution_deadline {
+            let earliest_resolution = match &market.resolver {
+                Resolver::BettorVote { .. } => market.voting_ends(),
+                Resolver::PriceFeed { condition, .. } => Some(condition.observation_time),
+                _ => None,
+            };
+            if earliest_resolution.is_some_and(|time| time >= deadline) {
+                return Err(PredictionMarketError::InvalidResolutionDeadline);
+            }
+        }
+
+        // A price-feed market is a yes/no question that must not be known while betting is open
+        if let Resolver::PriceFeed { condition, .. } = &market.resolver {
+            if market.options.len() != 2 || condition.observation_time < market.lock_time() {
+                return Err(PredictionMarketError::InvalidResolver);
+            }
+        }
+
+        // Escrow the creator bond
+        if creator_bond > Amount::ZERO {
+            Self::transfer_to_escrow(context, creator, creator_bond)?;
+        }
+
+        // Announce the market to the registry chain
+        self.announce(
+            context,
+            PredictionMarketEffect::RegistryMarketCreated {
+                summary: MarketSummary {
+                    market_id,
+                    question: market.question.clone(),
+                    status: market.status.clone(),
+                    end_time: market.end_time,
+                },
+            },
+        );
+
         // Store market
-        self.state.markets.insert(market_id, market);
-        self.state.next_market_id += 1;
+        self.state.markets.insert(&market_id, market)?;
+        self.state.next_market_id.set(index + 1);
 
         // Emit event
         Ok(vec![PredictionMarketEffect::MarketCreated {
@@ -265,209 +482,1634 @@ impl PredictionMarket {
     }
 
     /// Place a bet on a market option
-    fn place_bet(
+    async fn place_bet(
         &mut self,
         context: &linera_sdk::base::ContractRuntimeContext,
-        market_id: u64,
+        market_id: MarketId,
         option: String,
         amount: Amount,
-    ) -> Result<Vec<PredictionMarketEffect>, linera_sdk::base::ContractError> {
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get user
+        let user = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+
+        // Record the bet
+        let effect = self
+            .record_bet(market_id, user, option, amount, context.chain_id(), context.system_time())
+            .await?;
+
+        // Transfer tokens from user into the application escrow.
+        // If the transfer fails the whole operation is rejected and no state is changed.
+        Self::transfer_to_escrow(context, user, amount)?;
+
+        // Emit event
+        Ok(vec![effect])
+    }
+
+    /// Send a bet to a market hosted on another chain.
+    ///
+    /// The stake is transferred straight to the application escrow on the market chain,
+    /// and the bet is kept as outgoing until the market chain acknowledges or bounces it.
+    async fn place_remote_bet(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        option: String,
+        amount: Amount,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
         // Validate inputs
         if amount == Amount::ZERO {
-            return Err(linera_sdk::base::ContractError::Other("Bet amount must be greater than 0".to_string()));
+            return Err(PredictionMarketError::InvalidAmount);
         }
 
         // Get user
-        let user = context.authenticated_signer().ok_or_else(|| {
-            linera_sdk::base::ContractError::Other("Unauthorized: No authenticated signer".to_string())
-        })?;
+        let user = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+
+        // Markets on this chain are bet on directly
+        let origin = context.chain_id();
+        let market_chain = market_id.chain_id;
+        if market_chain == origin {
+            return self.place_bet(context, market_id, option, amount).await;
+        }
+
+        // Send the stake to the application escrow on the market chain
+        let escrow = Account {
+            chain_id: market_chain,
+            owner: Some(AccountOwner::Application(context.application_id())),
+        };
+        context
+            .transfer(Some(AccountOwner::User(user)), escrow, amount)
+            .map_err(|e| PredictionMarketError::TransferFailed {
+                amount,
+                reason: e.to_string(),
+            })?;
+
+        // Keep track of the bet until it is acknowledged
+        let outgoing_key = (market_id, user);
+        let mut outgoing = self.state.outgoing_bets.get(&outgoing_key).await?.unwrap_or_default();
+        outgoing.push(OutgoingBet {
+            market_id,
+            user,
+            option: option.clone(),
+            amount,
+            timestamp: context.system_time(),
+        });
+        self.state.outgoing_bets.insert(&outgoing_key, outgoing)?;
+
+        // Forward the bet to the market chain
+        context.send_effect(
+            market_chain,
+            PredictionMarketEffect::RemoteBet {
+                market_id,
+                user,
+                option,
+                amount,
+                origin,
+            },
+        );
+        Ok(vec![])
+    }
+
+    /// Apply a bet received from another chain and acknowledge it, or bounce the stake
+    /// back to the user if the bet is refused.
+    async fn receive_remote_bet(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+        origin: ChainId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        match self
+            .record_bet(market_id, user, option.clone(), amount, origin, context.system_time())
+            .await
+        {
+            Ok(effect) => {
+                context.send_effect(
+                    origin,
+                    PredictionMarketEffect::RemoteBetAccepted {
+                        market_id,
+                        user,
+                        option,
+                        amount,
+                    },
+                );
+                Ok(vec![effect])
+            }
+            Err(PredictionMarketError::Storage(reason)) => Err(PredictionMarketError::Storage(reason)),
+            Err(error) => {
+                // Return the stake to the user's account on their own chain
+                let destination = Account {
+                    chain_id: origin,
+                    owner: Some(AccountOwner::User(user)),
+                };
+                Self::transfer_from_escrow_to(context, destination, amount)?;
+                context.send_effect(
+                    origin,
+                    PredictionMarketEffect::RemoteBetRejected {
+                        market_id,
+                        user,
+                        amount,
+                        code: error.code().to_string(),
+                    },
+                );
+                Ok(vec![])
+            }
+        }
+    }
+
+    /// Drop the oldest outgoing bet of `user` on `market_id` once the market chain answered.
+    ///
+    /// Messages between two chains are delivered in order, so answers arrive in the order
+    /// the bets were sent.
+    async fn settle_outgoing_bet(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        user: Owner,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Only the market chain can answer for its markets
+        if context.message_origin_chain() != Some(market_id.chain_id) {
+            return Ok(vec![]);
+        }
+        let outgoing_key = (market_id, user);
+        let mut outgoing = self.state.outgoing_bets.get(&outgoing_key).await?.unwrap_or_default();
+        if !outgoing.is_empty() {
+            outgoing.remove(0);
+        }
+        if outgoing.is_empty() {
+            self.state.outgoing_bets.remove(&outgoing_key)?;
+        } else {
+            self.state.outgoing_bets.insert(&outgoing_key, outgoing)?;
+        }
+        Ok(vec![])
+    }
+
+    /// Withdraw stake placed from this chain on a market hosted on another chain.
+    ///
+    /// The market chain checks the request came from the chain the bets were placed from
+    /// and pays the stake back there, or answers with `RemoteRequestRejected`.
+    async fn withdraw_remote_bet(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        option: String,
+        amount: Amount,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let user = signer(context)?;
+
+        // Markets on this chain are handled directly
+        if market_id.chain_id == context.chain_id() {
+            return self.withdraw_bet(context, market_id, user, option, amount).await;
+        }
+        context.send_effect(
+            market_id.chain_id,
+            PredictionMarketEffect::RemoteWithdraw {
+                market_id,
+                user,
+                option,
+                amount,
+            },
+        );
+        Ok(vec![])
+    }
+
+    /// Vote with stake placed from this chain on a market hosted on another chain
+    async fn cast_remote_vote(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        option: String,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let user = signer(context)?;
+
+        // Markets on this chain are handled directly
+        if market_id.chain_id == context.chain_id() {
+            return self.cast_vote(context, market_id, user, option).await;
+        }
+        context.send_effect(
+            market_id.chain_id,
+            PredictionMarketEffect::RemoteVote {
+                market_id,
+                user,
+                option,
+            },
+        );
+        Ok(vec![])
+    }
+
+    /// Claim the reward of stake placed from this chain on a market hosted on another chain.
+    /// The market chain pays it to the user's account on this chain.
+    async fn claim_remote_reward(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let user = signer(context)?;
+
+        // Markets on this chain are handled directly
+        if market_id.chain_id == context.chain_id() {
+            return self.claim_reward(context, market_id, user).await;
+        }
+        context.send_effect(market_id.chain_id, PredictionMarketEffect::RemoteClaim { market_id, user });
+        Ok(vec![])
+    }
+
+    /// Claim the refund of stake placed from this chain on a market hosted on another chain.
+    /// The market chain pays it to the user's account on this chain.
+    async fn claim_remote_refund(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let user = signer(context)?;
+
+        // Markets on this chain are handled directly
+        if market_id.chain_id == context.chain_id() {
+            return self.claim_refund(context, market_id, user).await;
+        }
+        context.send_effect(market_id.chain_id, PredictionMarketEffect::RemoteRefund { market_id, user });
+        Ok(vec![])
+    }
+
+    /// Validate a bet placed from the `origin` chain and add it to the user's position and
+    /// the market totals.
+    ///
+    /// Funds are not moved here: local bets are debited by `place_bet`, remote bets arrive
+    /// with their funds already credited to the escrow. Nothing is written unless every
+    /// check passes. A position only holds bets placed from one chain, where it is paid out.
+    async fn record_bet(
+        &mut self,
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+        origin: ChainId,
+        now: Timestamp,
+    ) -> Result<PredictionMarketEffect, PredictionMarketError> {
+        // Validate inputs
+        if amount == Amount::ZERO {
+            return Err(PredictionMarketError::InvalidAmount);
+        }
+        if amount < self.parameters.min_bet {
+            return Err(PredictionMarketError::BetTooSmall);
+        }
+        if self.parameters.max_bet.is_some_and(|max_bet| amount > max_bet) {
+            return Err(PredictionMarketError::BetTooLarge);
+        }
 
         // Get market
-        let market = self.state.markets.get_mut(&market_id).ok_or_else(|| {
-            linera_sdk::base::ContractError::Other(format!("Market {} not found", market_id))
-        })?;
+        let mut market = self.load_market(market_id).await?;
 
         // Validate market
+        if now >= market.end_time {
+            return Err(PredictionMarketError::MarketEnded);
+        }
+        if market.refresh_status(now) {
+            return Err(PredictionMarketError::BettingClosed);
+        }
         if market.status != MarketStatus::Active {
-            return Err(linera_sdk::base::ContractError::Other("Market is not active".to_string()));
+            return Err(PredictionMarketError::MarketNotActive);
         }
-
-        let now = context.system_time();
-        if now >= market.end_time {
-            return Err(linera_sdk::base::ContractError::Other("Market has ended".to_string()));
+        if *self.state.betting_paused.get() || market.betting_paused {
+            return Err(PredictionMarketError::BettingPaused);
         }
 
         // Validate option
         if !market.options.contains(&option) {
-            return Err(linera_sdk::base::ContractError::Other(format!("Invalid option: {}", option)));
+            return Err(PredictionMarketError::InvalidOption(option));
         }
 
-        // Check if user already bet on this market
-        let bet_key = (market_id, user);
-        if self.state.user_bets.contains_key(&bet_key) {
-            return Err(linera_sdk::base::ContractError::Other("User already placed a bet on this market".to_string()));
+        // Load the user's position on this market, if any
+        let position_key = (market_id, user);
+        let existing = self.state.positions.get(&position_key).await?;
+        let is_new_position = existing.is_none();
+        let mut position = existing.unwrap_or_else(|| Position::new(market_id, user, origin));
+        if position.origin != origin {
+            return Err(PredictionMarketError::WrongOrigin);
         }
 
-        // Transfer tokens from user to contract
-        // Note: In Linera, token transfers are handled differently
-        // This is a simplified version - actual implementation would use token transfers
+        // Compute new totals before writing anything
+        let option_total = market.bets.get(&option).copied().unwrap_or(Amount::ZERO);
+        let option_total = payout::checked_add(option_total, amount)?;
+        let total_pool = payout::checked_add(market.total_pool, amount)?;
+        let position_stake = payout::checked_add(position.stake_on(&option), amount)?;
 
-        // Create bet
-        let bet = Bet {
+        // Record the bet in the user's position
+        position.stakes.insert(option.clone(), position_stake);
+        position.bets.push(Bet {
             market_id,
             user,
             option: option.clone(),
             amount,
             timestamp: now,
-            claimed: false,
-            reward_amount: Amount::ZERO,
-        };
+        });
+        self.state.positions.insert(&position_key, position)?;
 
-        // Store bet
-        self.state.user_bets.insert(bet_key, bet);
+        // Index the bettor under the market on their first bet
+        if is_new_position {
+            let mut bettors = self.state.bettors.get(&market_id).await?.unwrap_or_default();
+            bettors.push(user);
+            self.state.bettors.insert(&market_id, bettors)?;
+        }
 
         // Update market bets
-        let option_total = market.bets.get(&option).copied().unwrap_or(Amount::ZERO);
-        market.bets.insert(option, option_total + amount);
-        market.total_pool += amount;
+        market.bets.insert(option.clone(), option_total);
+        market.total_pool = total_pool;
+        self.state.markets.insert(&market_id, market)?;
 
-        // Emit event
-        Ok(vec![PredictionMarketEffect::BetPlaced {
+        Ok(PredictionMarketEffect::BetPlaced {
             market_id,
             user,
             option,
             amount,
-        }])
+        })
     }
 
-    /// Resolve a market (creator only)
-    fn resolve_market(
+    /// Withdraw stake from an option while the market is still active, paying it back on the
+    /// chain the bets were placed from.
+    ///
+    /// The withdrawal fee stays in the pool: it is removed from the option total but not
+    /// from `total_pool`, so it ends up in the winners' rewards, or is shared by refunds.
+    async fn withdraw_bet(
         &mut self,
         context: &linera_sdk::base::ContractRuntimeContext,
-        market_id: u64,
-        correct_answer: String,
-    ) -> Result<Vec<PredictionMarketEffect>, linera_sdk::base::ContractError> {
-        // Get caller
-        let caller = context.authenticated_signer().ok_or_else(|| {
-            linera_sdk::base::ContractError::Other("Unauthorized: No authenticated signer".to_string())
-        })?;
+        market_id: MarketId,
+        user: Owner,
+        option: String,
+        amount: Amount,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Validate inputs
+        if amount == Amount::ZERO {
+            return Err(PredictionMarketError::InvalidAmount);
+        }
 
         // Get market
-        let market = self.state.markets.get_mut(&market_id).ok_or_else(|| {
-            linera_sdk::base::ContractError::Other(format!("Market {} not found", market_id))
-        })?;
+        let mut market = self.load_market(market_id).await?;
+        let now = context.system_time();
 
-        // Validate caller is creator
-        if market.creator != caller {
-            return Err(linera_sdk::base::ContractError::Other("Only creator can resolve market".to_string()));
+        // Validate market
+        if market.refresh_status(now) {
+            return Err(PredictionMarketError::BettingClosed);
         }
-
-        // Validate market can be resolved
-        if market.status == MarketStatus::Resolved {
-            return Err(linera_sdk::base::ContractError::Other("Market already resolved".to_string()));
+        if market.status != MarketStatus::Active {
+            return Err(PredictionMarketError::MarketNotActive);
         }
 
-        let now = context.system_time();
-        if now < market.end_time {
-            return Err(linera_sdk::base::ContractError::Other("Market has not ended yet".to_string()));
-        }
+        // Get user position
+        let position_key = (market_id, user);
+        let mut position = self
+            .state
+            .positions
+            .get(&position_key)
+            .await?
+            .ok_or(PredictionMarketError::NoBet)?;
+        check_origin(context, &position)?;
 
-        // Validate correct answer
-        if !market.options.contains(&correct_answer) {
-            return Err(linera_sdk::base::ContractError::Other(format!("Invalid correct answer: {}", correct_answer)));
+        // Compute new totals before moving any funds
+        let position_stake = payout::checked_sub(position.stake_on(&option), amount)
+            .map_err(|_| PredictionMarketError::InsufficientStake)?;
+        let option_total = market.bets.get(&option).copied().unwrap_or(Amount::ZERO);
+        let option_total = payout::checked_sub(option_total, amount)?;
+        let fee = payout::fee(amount, market.withdrawal_fee_bps)?;
+        let returned = payout::checked_sub(amount, fee)?;
+        let total_pool = payout::checked_sub(market.total_pool, returned)?;
+        let withdrawal_fees = payout::checked_add(market.withdrawal_fees, fee)?;
+
+        // Return the stake minus the fee from the application escrow.
+        // If the transfer fails the whole operation is rejected and no state is changed.
+        if returned > Amount::ZERO {
+            Self::pay_bettor(context, &position, returned)?;
         }
 
-        // Update market status
-        market.status = MarketStatus::Resolved;
+        // Record the withdrawal in the user's position
+        position.stakes.insert(option.clone(), position_stake);
+        position.withdrawals.push(Withdrawal {
+            option: option.clone(),
+            amount,
+            fee,
+            timestamp: now,
+        });
+        self.state.positions.insert(&position_key, position)?;
+
+        // Update market bets
+        market.bets.insert(option.clone(), option_total);
+        market.total_pool = total_pool;
+        market.withdrawal_fees = withdrawal_fees;
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit event
+        Ok(vec![PredictionMarketEffect::BetWithdrawn {
+            market_id,
+            user,
+            option,
+            amount,
+            fee,
+        }])
+    }
+
+    /// Resolve a market on behalf of its designated resolver.
+    ///
+    /// A committee member's answer is recorded as a vote; the market only resolves once
+    /// `quorum` members submitted the same answer. Markets with dispute settings do not
+    /// resolve yet: the answer is proposed and the signer posts the bond.
+    async fn resolve_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        correct_answer: String,
+        evidence: Evidence,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate caller is the designated resolver
+        let signer = context.authenticated_signer();
+        let allowed = match &market.resolver {
+            Resolver::Creator => signer == Some(market.creator),
+            Resolver::Owner { owner } => signer == Some(*owner),
+            Resolver::Committee { members, .. } => signer.is_some_and(|signer| members.contains(&signer)),
+            Resolver::Oracle { application_id } => context.authenticated_caller_id() == Some(*application_id),
+            // Voted and price-feed markets are settled by `CloseVote` and `ResolveFromFeed`
+            Resolver::BettorVote { .. } | Resolver::PriceFeed { .. } => false,
+        };
+        if !allowed {
+            return Err(PredictionMarketError::NotResolver);
+        }
+
+        // Validate market can be resolved
+        let now = context.system_time();
+        check_unresolved(&market, now)?;
+        if matches!(market.status, MarketStatus::Proposed | MarketStatus::Disputed) {
+            return Err(PredictionMarketError::ResolutionPending);
+        }
+        if now < market.end_time {
+            return Err(PredictionMarketError::MarketNotEnded);
+        }
+
+        // Validate correct answer and evidence
+        if !market.options.contains(&correct_answer) {
+            return Err(PredictionMarketError::InvalidOption(correct_answer));
+        }
+        if evidence.source.is_empty() {
+            return Err(PredictionMarketError::EmptyEvidence);
+        }
+        market.refresh_status(now);
+
+        // Committee members vote until enough of them agree
+        if let (Resolver::Committee { quorum, .. }, Some(voter)) = (&market.resolver, signer) {
+            let quorum = *quorum as usize;
+            market.resolution_votes.insert(voter, correct_answer.clone());
+            let agreeing = market
+                .resolution_votes
+                .values()
+                .filter(|answer| **answer == correct_answer)
+                .count();
+            if agreeing < quorum {
+                self.state.markets.insert(&market_id, market)?;
+                return Ok(vec![PredictionMarketEffect::ResolutionVoteCast {
+                    market_id,
+                    voter,
+                    answer: correct_answer,
+                }]);
+            }
+        }
+
+        // Keep the evidence of the answer that resolves, or is proposed for, the market
+        market.resolution_evidence = Some(evidence);
+
+        // With a challenge window the answer is only proposed, backed by the signer's bond
+        if let Some(settings) = market.dispute_settings.clone() {
+            let proposer = signer.ok_or(PredictionMarketError::Unauthorized)?;
+            Self::transfer_to_escrow(context, proposer, settings.bond)?;
+            let challenge_ends = add_minutes(now, settings.challenge_minutes)?;
+            market.status = MarketStatus::Proposed;
+            market.proposal = Some(Proposal {
+                answer: correct_answer.clone(),
+                proposer,
+                proposed_at: now,
+                challenge_ends,
+                disputer: None,
+            });
+            self.announce_status(context, &market);
+            self.state.markets.insert(&market_id, market)?;
+            return Ok(vec![PredictionMarketEffect::ResolutionProposed {
+                market_id,
+                proposer,
+                answer: correct_answer,
+                bond: settings.bond,
+                challenge_ends,
+            }]);
+        }
+
+        // Settle the market
+        let effects = self.settle_resolution(context, &mut market, correct_answer, false).await?;
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Dispute the proposed answer of a market, posting a bond matching the proposer's
+    async fn dispute_resolution(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get disputer
+        let disputer = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate the proposal can still be disputed
+        if market.status != MarketStatus::Proposed {
+            return Err(PredictionMarketError::NotProposed);
+        }
+        let (Some(settings), Some(proposal)) = (market.dispute_settings.clone(), market.proposal.as_mut()) else {
+            return Err(PredictionMarketError::NotProposed);
+        };
+        if context.system_time() >= proposal.challenge_ends {
+            return Err(PredictionMarketError::ChallengeWindowClosed);
+        }
+
+        // Post the matching bond
+        Self::transfer_to_escrow(context, disputer, settings.bond)?;
+        proposal.disputer = Some(disputer);
+        market.status = MarketStatus::Disputed;
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit event
+        Ok(vec![PredictionMarketEffect::ResolutionDisputed {
+            market_id,
+            disputer,
+            bond: settings.bond,
+        }])
+    }
+
+    /// Make an undisputed proposal final once its challenge window closed, returning the
+    /// proposer's bond
+    async fn finalize_resolution(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate the challenge window is over
+        if market.status != MarketStatus::Proposed {
+            return Err(PredictionMarketError::NotProposed);
+        }
+        let (Some(settings), Some(proposal)) = (market.dispute_settings.clone(), market.proposal.clone()) else {
+            return Err(PredictionMarketError::NotProposed);
+        };
+        if context.system_time() < proposal.challenge_ends {
+            return Err(PredictionMarketError::ChallengeWindowOpen);
+        }
+
+        // Return the bond and settle the market with the proposed answer
+        Self::transfer_from_escrow(context, proposal.proposer, settings.bond)?;
+        let mut effects = vec![PredictionMarketEffect::BondReturned {
+            market_id,
+            owner: proposal.proposer,
+            amount: settings.bond,
+        }];
+        effects.extend(self.settle_resolution(context, &mut market, proposal.answer, false).await?);
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Rule on a disputed answer and settle the market with the ruling.
+    ///
+    /// If the ruling confirms the proposed answer the proposer wins both bonds, otherwise
+    /// the disputer does and the overturned resolution slashes the creator bond. The
+    /// arbiter's evidence replaces the proposer's either way.
+    async fn arbitrate_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        correct_answer: String,
+        evidence: Evidence,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get caller
+        let caller = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate caller is the arbiter of a disputed market
+        let (Some(settings), Some(proposal)) = (market.dispute_settings.clone(), market.proposal.clone()) else {
+            return Err(PredictionMarketError::NotDisputed);
+        };
+        if settings.arbiter != caller {
+            return Err(PredictionMarketError::NotArbiter);
+        }
+        let Some(disputer) = proposal.disputer.filter(|_| market.status == MarketStatus::Disputed) else {
+            return Err(PredictionMarketError::NotDisputed);
+        };
+        check_unresolved(&market, context.system_time())?;
+
+        // Validate correct answer and evidence
+        if !market.options.contains(&correct_answer) {
+            return Err(PredictionMarketError::InvalidOption(correct_answer));
+        }
+        if evidence.source.is_empty() {
+            return Err(PredictionMarketError::EmptyEvidence);
+        }
+        market.resolution_evidence = Some(evidence);
+
+        // Slash the losing bond to the winning side
+        let overturned = correct_answer != proposal.answer;
+        let winner = if overturned { disputer } else { proposal.proposer };
+        let bonds = payout::checked_add(settings.bond, settings.bond)?;
+        Self::transfer_from_escrow(context, winner, bonds)?;
+        let mut effects = vec![PredictionMarketEffect::DisputeSettled {
+            market_id,
+            winner,
+            amount: bonds,
+        }];
+        effects.extend(
+            self.settle_resolution(context, &mut market, correct_answer, overturned)
+                .await?,
+        );
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Record a bettor's vote on the correct answer, weighted by their total stake
+    async fn cast_vote(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        voter: Owner,
+        option: String,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate the voting window is open
+        let voting_ends = market.voting_ends().ok_or(PredictionMarketError::NoVoting)?;
+        let now = context.system_time();
+        check_unresolved(&market, now)?;
+        if now < market.end_time {
+            return Err(PredictionMarketError::MarketNotEnded);
+        }
+        if now >= voting_ends {
+            return Err(PredictionMarketError::VotingClosed);
+        }
+
+        // Validate option
+        if !market.options.contains(&option) {
+            return Err(PredictionMarketError::InvalidOption(option));
+        }
+
+        // Weigh the vote by the voter's stake, which cannot change after the end time
+        let position = self
+            .state
+            .positions
+            .get(&(market_id, voter))
+            .await?
+            .ok_or(PredictionMarketError::NoBet)?;
+        check_origin(context, &position)?;
+        let weight = position.total_stake();
+        if weight == Amount::ZERO {
+            return Err(PredictionMarketError::NoBet);
+        }
+
+        // Replace any previous vote in the tally
+        let vote_key = (market_id, voter);
+        if let Some(previous) = self.state.votes.get(&vote_key).await? {
+            let tally = market.vote_tally.entry(previous.option).or_insert(Amount::ZERO);
+            *tally = payout::checked_sub(*tally, previous.weight)?;
+        }
+        let tally = market.vote_tally.entry(option.clone()).or_insert(Amount::ZERO);
+        *tally = payout::checked_add(*tally, weight)?;
+        market.refresh_status(now);
+        self.state.votes.insert(
+            &vote_key,
+            Vote {
+                market_id,
+                voter,
+                option: option.clone(),
+                weight,
+                timestamp: now,
+            },
+        )?;
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit event
+        Ok(vec![PredictionMarketEffect::VoteCast {
+            market_id,
+            voter,
+            option,
+            weight,
+        }])
+    }
+
+    /// Settle a market resolved by vote once its voting window closed.
+    ///
+    /// The option with the most stake-weighted votes becomes the correct answer. If the
+    /// top options tie, or nobody voted, the market is cancelled so every bettor is refunded.
+    async fn close_vote(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate the voting window is over
+        let voting_ends = market.voting_ends().ok_or(PredictionMarketError::NoVoting)?;
+        let now = context.system_time();
+        check_unresolved(&market, now)?;
+        if now < voting_ends {
+            return Err(PredictionMarketError::VotingOpen);
+        }
+        market.refresh_status(now);
+
+        // Find the plurality option
+        let top = market.vote_tally.values().copied().max().unwrap_or(Amount::ZERO);
+        let leaders: Vec<String> = market
+            .vote_tally
+            .iter()
+            .filter(|(_, weight)| top > Amount::ZERO && **weight == top)
+            .map(|(option, _)| option.clone())
+            .collect();
+
+        let effects = if let [correct_answer] = leaders.as_slice() {
+            self.settle_resolution(context, &mut market, correct_answer.clone(), false)
+                .await?
+        } else {
+            market.status = MarketStatus::Cancelled;
+            market.keep_unshared_withdrawal_fees()?;
+            let mut effects = vec![PredictionMarketEffect::VoteTied { market_id }];
+            effects.extend(self.settle_creator_bond(context, &mut market, false).await?);
+            effects
+        };
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Resolve a price-feed market by reading the feed price at its observation time
+    async fn resolve_from_feed(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+        let Resolver::PriceFeed {
+            application_id,
+            condition,
+        } = market.resolver.clone()
+        else {
+            return Err(PredictionMarketError::NoPriceFeed);
+        };
+
+        // Validate market can be resolved
+        let now = context.system_time();
+        check_unresolved(&market, now)?;
+        if now < market.end_time {
+            return Err(PredictionMarketError::MarketNotEnded);
+        }
+        market.refresh_status(now);
+
+        // Read the observed price from the feed application
+        let observation: Option<PriceObservation> = context
+            .call_application(
+                false,
+                application_id,
+                &PriceFeedCall::ObservationAt {
+                    feed_id: condition.feed_id.clone(),
+                    time: condition.observation_time,
+                },
+            )
+            .map_err(|e| PredictionMarketError::OracleCallFailed(e.to_string()))?;
+        let observation = observation.ok_or(PredictionMarketError::PriceNotAvailable)?;
+
+        // The first option means the condition holds
+        let outcome = if condition.comparator.holds(observation.price, condition.threshold) {
+            0
+        } else {
+            1
+        };
+        let correct_answer = market.options[outcome].clone();
+        let mut effects = vec![PredictionMarketEffect::PriceObserved {
+            market_id,
+            price: observation.price,
+            timestamp: observation.timestamp,
+        }];
+        effects.extend(self.settle_resolution(context, &mut market, correct_answer, false).await?);
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Resolve `market` with its final answer, compute the winners' rewards and settle the
+    /// creator bond.
+    ///
+    /// Positions are written back here; the caller saves the market itself.
+    async fn settle_resolution(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market: &mut Market,
+        correct_answer: String,
+        creator_at_fault: bool,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let market_id = market.id;
+
+        // Update market status
+        market.status = MarketStatus::Resolved;
         market.correct_answer = Some(correct_answer.clone());
+        let mut effects = vec![PredictionMarketEffect::MarketResolved {
+            market_id,
+            correct_answer: correct_answer.clone(),
+        }];
 
         // Calculate rewards for winners
         let winning_bet_amount = market.bets.get(&correct_answer).copied().unwrap_or(Amount::ZERO);
-        if winning_bet_amount > Amount::ZERO {
-            // Calculate rewards proportionally
-            let total_pool = market.total_pool;
-            for (bet_key, bet) in self.state.user_bets.iter_mut() {
-                if bet_key.0 == market_id && bet.option == correct_answer {
-                    // Calculate reward: (bet_amount / winning_pool) * total_pool
-                    // But cap at max_reward
-                    let reward = if total_pool > Amount::ZERO && winning_bet_amount > Amount::ZERO {
-                        (bet.amount * total_pool) / winning_bet_amount
-                    } else {
-                        Amount::ZERO
-                    };
-                    let capped_reward = if reward > market.max_reward {
-                        market.max_reward
-                    } else {
-                        reward
-                    };
-                    bet.reward_amount = capped_reward;
+        if winning_bet_amount == Amount::ZERO {
+            // Nobody backed the correct answer: apply the market's no-winner policy
+            market.status = MarketStatus::ResolvedNoWinners;
+            if market.no_winner_policy == NoWinnerPolicy::SendToTreasury {
+                match self.parameters.treasury {
+                    Some(treasury) if market.total_pool > Amount::ZERO => {
+                        Self::transfer_from_escrow(context, treasury, market.total_pool)?;
+                        effects.push(PredictionMarketEffect::PoolSentToTreasury {
+                            market_id,
+                            treasury,
+                            amount: market.total_pool,
+                        });
+                    }
+                    Some(_) => {}
+                    // Markets migrated from an application without a treasury refund their bettors
+                    None => market.no_winner_policy = NoWinnerPolicy::RefundBettors,
+                }
+            }
+            if market.no_winner_policy == NoWinnerPolicy::RefundBettors {
+                market.keep_unshared_withdrawal_fees()?;
+            }
+        } else {
+            // Load the winning positions of this market only
+            let mut winners = Vec::new();
+            let bettors = self.state.bettors.get(&market_id).await?.unwrap_or_default();
+            for user in bettors {
+                let position_key = (market_id, user);
+                if let Some(position) = self.state.positions.get(&position_key).await? {
+                    if position.stake_on(&correct_answer) > Amount::ZERO {
+                        winners.push((position_key, position));
+                    }
+                }
+            }
+
+            // Take the protocol and creator fees off the pool
+            market.protocol_fee = payout::fee(market.total_pool, market.protocol_fee_bps)?;
+            market.creator_fee = payout::fee(market.total_pool, market.creator_fee_bps)?;
+            let distributable = payout::checked_sub(
+                market.total_pool,
+                payout::checked_add(market.protocol_fee, market.creator_fee)?,
+            )?;
+            effects.extend(self.accrue_fees(market).await?);
+
+            // Calculate rewards in proportion to the stake on the correct answer, capped at max_reward
+            let stakes: Vec<Amount> = winners
+                .iter()
+                .map(|(_, position)| position.stake_on(&correct_answer))
+                .collect();
+            let distribution = payout::winner_rewards(
+                &stakes,
+                distributable,
+                winning_bet_amount,
+                market.max_reward,
+                market.surplus_policy == SurplusPolicy::RedistributeToWinners,
+            )?;
+            for ((position_key, mut position), reward) in winners.into_iter().zip(distribution.rewards) {
+                position.reward_amount = reward;
+                self.state.positions.insert(&position_key, position)?;
+            }
+
+            // Pay out whatever the cap withheld according to the market's surplus policy.
+            // Rounding dust stays in escrow and is recorded on the market.
+            market.surplus = distribution.surplus;
+            market.dust = distribution.dust;
+            let recipient = match market.surplus_policy {
+                SurplusPolicy::ReturnToCreator => Some(market.creator),
+                SurplusPolicy::RedistributeToWinners | SurplusPolicy::SendToTreasury => self.parameters.treasury,
+            };
+            match recipient {
+                Some(recipient) if market.surplus > Amount::ZERO => {
+                    Self::transfer_from_escrow(context, recipient, market.surplus)?;
+                    effects.push(PredictionMarketEffect::SurplusPaid {
+                        market_id,
+                        recipient,
+                        amount: market.surplus,
+                    });
+                }
+                Some(_) => {}
+                // Without a treasury, what is left once every winner is capped stays in escrow
+                None => {
+                    market.dust = payout::checked_add(market.dust, market.surplus)?;
+                    market.surplus = Amount::ZERO;
                 }
             }
         }
+        effects.extend(self.settle_creator_bond(context, market, creator_at_fault).await?);
+        Ok(effects)
+    }
 
-        // Emit event
-        Ok(vec![PredictionMarketEffect::MarketResolved {
-            market_id,
-            correct_answer,
+    /// Credit the fees taken off the pool of `market` to the treasury and the creator.
+    ///
+    /// Fees stay in escrow until their recipient sends `WithdrawFees`.
+    async fn accrue_fees(
+        &mut self,
+        market: &Market,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        if market.protocol_fee == Amount::ZERO && market.creator_fee == Amount::ZERO {
+            return Ok(vec![]);
+        }
+        if market.protocol_fee > Amount::ZERO {
+            // Validated parameters always name a treasury when the protocol fee is set
+            let treasury = self.parameters.treasury.ok_or_else(|| {
+                PredictionMarketError::InvalidParameters("a protocol fee requires a treasury".to_string())
+            })?;
+            self.credit_fee(treasury, market.protocol_fee).await?;
+        }
+        if market.creator_fee > Amount::ZERO {
+            self.credit_fee(market.creator, market.creator_fee).await?;
+        }
+        Ok(vec![PredictionMarketEffect::FeesAccrued {
+            market_id: market.id,
+            protocol_fee: market.protocol_fee,
+            creator_fee: market.creator_fee,
         }])
     }
 
-    /// Claim reward from a resolved market
-    fn claim_reward(
+    /// Add `amount` to the fee balance of `owner`
+    async fn credit_fee(&mut self, owner: Owner, amount: Amount) -> Result<(), PredictionMarketError> {
+        let balance = self.state.fee_balances.get(&owner).await?.unwrap_or(Amount::ZERO);
+        self.state.fee_balances.insert(&owner, payout::checked_add(balance, amount)?)?;
+        Ok(())
+    }
+
+    /// Pay out every fee accrued to the signer
+    async fn withdraw_fees(
         &mut self,
         context: &linera_sdk::base::ContractRuntimeContext,
-        market_id: u64,
-    ) -> Result<Vec<PredictionMarketEffect>, linera_sdk::base::ContractError> {
-        // Get user
-        let user = context.authenticated_signer().ok_or_else(|| {
-            linera_sdk::base::ContractError::Other("Unauthorized: No authenticated signer".to_string())
-        })?;
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get owner
+        let owner = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+
+        // Get balance
+        let amount = self.state.fee_balances.get(&owner).await?.unwrap_or(Amount::ZERO);
+        if amount == Amount::ZERO {
+            return Err(PredictionMarketError::NoFees);
+        }
+
+        // Pay from the application escrow.
+        // If the transfer fails the whole operation is rejected and the balance is kept.
+        Self::transfer_from_escrow(context, owner, amount)?;
+        self.state.fee_balances.remove(&owner)?;
+
+        // Emit event
+        Ok(vec![PredictionMarketEffect::FeesWithdrawn { owner, amount }])
+    }
 
+    /// Return the creator bond of `market`, or share it between the bettors in proportion
+    /// to their total stake when the creator is at fault.
+    ///
+    /// Attos of a slashed bond that cannot be shared are added to the market's dust.
+    async fn settle_creator_bond(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market: &mut Market,
+        at_fault: bool,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let market_id = market.id;
+        let bond = market.creator_bond;
+        if bond == Amount::ZERO {
+            return Ok(vec![]);
+        }
+        market.creator_bond = Amount::ZERO;
+
+        if !at_fault {
+            Self::transfer_from_escrow(context, market.creator, bond)?;
+            return Ok(vec![PredictionMarketEffect::BondReturned {
+                market_id,
+                owner: market.creator,
+                amount: bond,
+            }]);
+        }
+
+        // Share the bond between every bettor of this market, on the chains they bet from
+        let bettors = self.state.bettors.get(&market_id).await?.unwrap_or_default();
+        let mut positions = Vec::with_capacity(bettors.len());
+        for user in &bettors {
+            if let Some(position) = self.state.positions.get(&(market_id, *user)).await? {
+                positions.push(position);
+            }
+        }
+        let stakes: Vec<Amount> = positions.iter().map(Position::total_stake).collect();
+        let (shares, dust) = payout::pro_rata(&stakes, bond)?;
+        for (position, share) in positions.iter().zip(shares) {
+            if share > Amount::ZERO {
+                Self::pay_bettor(context, position, share)?;
+            }
+        }
+        market.dust = payout::checked_add(market.dust, dust)?;
+        Ok(vec![PredictionMarketEffect::CreatorBondSlashed { market_id, amount: bond }])
+    }
+
+    /// Expire a market left unresolved past its resolution deadline so every bettor can
+    /// reclaim their stake.
+    ///
+    /// A disputed market the arbiter never ruled on expires too, and both bonds are returned.
+    /// An undisputed proposal is not expired: it can still be finalized. If the creator was
+    /// the resolver, the abandoned market slashes their bond.
+    async fn expire_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate the deadline passed without resolution
+        let now = context.system_time();
+        match check_unresolved(&market, now) {
+            Err(PredictionMarketError::ResolutionDeadlinePassed) => {}
+            Err(error) => return Err(error),
+            Ok(()) => return Err(PredictionMarketError::DeadlineNotReached),
+        }
+        if market.status == MarketStatus::Proposed {
+            return Err(PredictionMarketError::ResolutionPending);
+        }
+
+        // Return the bonds of an unsettled dispute
+        let mut effects = Self::return_dispute_bonds(context, &market)?;
+
+        // Update market status
+        market.status = MarketStatus::Expired;
+        market.keep_unshared_withdrawal_fees()?;
+        effects.push(PredictionMarketEffect::MarketExpired { market_id });
+        let creator_at_fault = market.resolver == Resolver::Creator;
+        effects.extend(self.settle_creator_bond(context, &mut market, creator_at_fault).await?);
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Claim reward from a resolved market
+    async fn claim_reward(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        user: Owner,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
         // Get market
-        let market = self.state.markets.get(&market_id).ok_or_else(|| {
-            linera_sdk::base::ContractError::Other(format!("Market {} not found", market_id))
-        })?;
+        let market = self.load_market(market_id).await?;
 
         // Validate market is resolved
-        if market.status != MarketStatus::Resolved {
-            return Err(linera_sdk::base::ContractError::Other("Market is not resolved".to_string()));
+        match market.status {
+            MarketStatus::Resolved => {}
+            MarketStatus::ResolvedNoWinners => return Err(PredictionMarketError::NotWinner),
+            _ => return Err(PredictionMarketError::NotResolved),
         }
 
-        // Get user bet
-        let bet_key = (market_id, user);
-        let bet = self.state.user_bets.get_mut(&bet_key).ok_or_else(|| {
-            linera_sdk::base::ContractError::Other("User has no bet on this market".to_string())
-        })?;
+        // Get user position
+        let position_key = (market_id, user);
+        let mut position = self
+            .state
+            .positions
+            .get(&position_key)
+            .await?
+            .ok_or(PredictionMarketError::NoBet)?;
+        check_origin(context, &position)?;
 
         // Validate user won
-        if bet.option != market.correct_answer.as_ref().unwrap() {
-            return Err(linera_sdk::base::ContractError::Other("User did not win this market".to_string()));
+        let correct_answer = market.correct_answer.as_deref().unwrap_or_default();
+        if position.stake_on(correct_answer) == Amount::ZERO {
+            return Err(PredictionMarketError::NotWinner);
         }
 
         // Validate reward not already claimed
-        if bet.claimed {
-            return Err(linera_sdk::base::ContractError::Other("Reward already claimed".to_string()));
+        if position.claimed {
+            return Err(PredictionMarketError::AlreadyClaimed);
         }
 
         // Validate reward amount
-        if bet.reward_amount == Amount::ZERO {
-            return Err(linera_sdk::base::ContractError::Other("No reward available".to_string()));
+        if position.reward_amount == Amount::ZERO {
+            return Err(PredictionMarketError::NothingToClaim);
         }
 
-        // Mark as claimed
-        bet.claimed = true;
+        // Transfer reward from the application escrow to the user, on the chain they bet from.
+        // If the transfer fails the whole operation is rejected and the position stays unclaimed.
+        Self::pay_bettor(context, &position, position.reward_amount)?;
 
-        // Transfer reward to user
-        // Note: In Linera, token transfers are handled differently
-        // This is a simplified version - actual implementation would use token transfers
+        // Mark as claimed
+        position.claimed = true;
+        let amount = position.reward_amount;
+        self.state.positions.insert(&position_key, position)?;
 
         // Emit event
         Ok(vec![PredictionMarketEffect::RewardClaimed {
             market_id,
             user,
-            amount: bet.reward_amount,
+            amount,
+        }])
+    }
+
+    /// Lock a market once its betting window has closed
+    async fn lock_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate market can be locked
+        if market.status != MarketStatus::Active {
+            return Err(PredictionMarketError::MarketNotActive);
+        }
+        if !market.refresh_status(context.system_time()) {
+            return Err(PredictionMarketError::LockNotDue);
+        }
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit event
+        Ok(vec![PredictionMarketEffect::MarketLocked { market_id }])
+    }
+
+    /// Cancel a market so that every bettor can reclaim their stake.
+    ///
+    /// An admin cancelling `at_fault` slashes the creator bond to the bettors; otherwise
+    /// it is returned to the creator.
+    async fn cancel_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        at_fault: bool,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get caller
+        let caller = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate market can be cancelled
+        match market.status {
+            MarketStatus::Resolved | MarketStatus::ResolvedNoWinners => {
+                return Err(PredictionMarketError::AlreadyResolved)
+            }
+            MarketStatus::Cancelled => return Err(PredictionMarketError::MarketCancelled),
+            MarketStatus::Expired => return Err(PredictionMarketError::MarketExpired),
+            // Bonds are escrowed until the proposal is final or arbitrated
+            MarketStatus::Proposed | MarketStatus::Disputed => {
+                return Err(PredictionMarketError::ResolutionPending)
+            }
+            MarketStatus::Active | MarketStatus::Locked => {}
+        }
+
+        // Validate caller: the creator while nobody has bet yet, or any admin
+        let is_admin = self.is_admin(caller).await?;
+        let creator_may_cancel = market.creator == caller && market.total_pool == Amount::ZERO;
+        if !is_admin && (!creator_may_cancel || at_fault) {
+            return Err(PredictionMarketError::CancelNotAllowed);
+        }
+
+        // Update market status
+        market.status = MarketStatus::Cancelled;
+        market.keep_unshared_withdrawal_fees()?;
+        let mut effects = vec![PredictionMarketEffect::MarketCancelled {
+            market_id,
+            cancelled_by: caller,
+        }];
+        effects.extend(self.settle_creator_bond(context, &mut market, at_fault).await?);
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Pause or resume market creation on this chain
+    async fn set_creation_paused(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        paused: bool,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let admin = self.check_admin(context).await?;
+        self.state.creation_paused.set(paused);
+        Ok(vec![PredictionMarketEffect::CreationPauseSet { paused, admin }])
+    }
+
+    /// Pause or resume betting on every market of this chain
+    async fn set_betting_paused(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        paused: bool,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let admin = self.check_admin(context).await?;
+        self.state.betting_paused.set(paused);
+        Ok(vec![PredictionMarketEffect::BettingPauseSet { paused, admin }])
+    }
+
+    /// Pause or resume betting on one market
+    async fn set_market_betting_paused(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        paused: bool,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let admin = self.check_admin(context).await?;
+        let mut market = self.load_market(market_id).await?;
+        market.betting_paused = paused;
+        self.state.markets.insert(&market_id, market)?;
+        Ok(vec![PredictionMarketEffect::MarketBettingPauseSet {
+            market_id,
+            paused,
+            admin,
         }])
     }
+
+    /// Cancel an unresolved market whatever its resolution progress.
+    ///
+    /// Unlike `CancelMarket`, this also stops a proposed or disputed answer: the bonds
+    /// posted on it are returned.
+    async fn force_cancel_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        at_fault: bool,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let admin = self.check_admin(context).await?;
+
+        // Get market
+        let mut market = self.load_market(market_id).await?;
+
+        // Validate market is unresolved
+        match market.status {
+            MarketStatus::Resolved | MarketStatus::ResolvedNoWinners => {
+                return Err(PredictionMarketError::AlreadyResolved)
+            }
+            MarketStatus::Cancelled => return Err(PredictionMarketError::MarketCancelled),
+            MarketStatus::Expired => return Err(PredictionMarketError::MarketExpired),
+            MarketStatus::Active
+            | MarketStatus::Locked
+            | MarketStatus::Proposed
+            | MarketStatus::Disputed => {}
+        }
+
+        // Return the bonds of a pending proposal or dispute
+        let mut effects = Self::return_dispute_bonds(context, &market)?;
+        market.proposal = None;
+
+        // Update market status
+        market.status = MarketStatus::Cancelled;
+        market.keep_unshared_withdrawal_fees()?;
+        effects.push(PredictionMarketEffect::MarketForceCancelled { market_id, admin });
+        effects.extend(self.settle_creator_bond(context, &mut market, at_fault).await?);
+        self.announce_status(context, &market);
+        self.state.markets.insert(&market_id, market)?;
+
+        // Emit events
+        Ok(effects)
+    }
+
+    /// Grant admin rights on this chain
+    async fn add_admin(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        owner: Owner,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let added_by = self.check_admin(context).await?;
+        self.state.admins.insert(&owner)?;
+        Ok(vec![PredictionMarketEffect::AdminAdded { owner, added_by }])
+    }
+
+    /// Revoke admin rights on this chain.
+    ///
+    /// Together with `AddAdmin` this rotates admins; the chain must keep at least one admin
+    /// unless the application-wide `admin` parameter is set.
+    async fn remove_admin(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        owner: Owner,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let removed_by = self.check_admin(context).await?;
+        let admins = self.state.admins.indices().await?;
+        if !admins.contains(&owner) {
+            return Err(PredictionMarketError::NotAdmin);
+        }
+        if admins.len() == 1 && self.parameters.admin.is_none() {
+            return Err(PredictionMarketError::LastAdmin);
+        }
+        self.state.admins.remove(&owner)?;
+        Ok(vec![PredictionMarketEffect::AdminRemoved { owner, removed_by }])
+    }
+
+    /// Refund a user's stake from a cancelled market or a market nobody won
+    async fn claim_refund(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        user: Owner,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        // Get market
+        let market = self.load_market(market_id).await?;
+
+        // Get user position
+        let position_key = (market_id, user);
+        let mut position = self
+            .state
+            .positions
+            .get(&position_key)
+            .await?
+            .ok_or(PredictionMarketError::NoBet)?;
+        check_origin(context, &position)?;
+
+        // Validate refund not already claimed
+        if position.claimed {
+            return Err(PredictionMarketError::AlreadyClaimed);
+        }
+
+        // Validate market is refundable
+        let Some(Claimable::Refund(amount)) = position.claimable(&market)? else {
+            return Err(PredictionMarketError::NotRefundable);
+        };
+
+        // Return the whole stake, with its share of the withdrawal fees, from the application
+        // escrow to the chain the user bet from.
+        // If the transfer fails the whole operation is rejected and the position stays unclaimed.
+        Self::pay_bettor(context, &position, amount)?;
+
+        // Mark as claimed
+        position.claimed = true;
+        self.state.positions.insert(&position_key, position)?;
+
+        // Emit event
+        Ok(vec![PredictionMarketEffect::RefundClaimed {
+            market_id,
+            user,
+            amount,
+        }])
+    }
+
+    /// Send an announcement to the registry chain, if one is configured
+    fn announce(&self, context: &linera_sdk::base::ContractRuntimeContext, announcement: PredictionMarketEffect) {
+        if let Some(registry_chain) = self.parameters.registry_chain {
+            context.send_effect(registry_chain, announcement);
+        }
+    }
+
+    /// Announce the current status of `market` to the registry chain
+    fn announce_status(&self, context: &linera_sdk::base::ContractRuntimeContext, market: &Market) {
+        self.announce(
+            context,
+            PredictionMarketEffect::RegistryMarketUpdated {
+                market_id: market.id,
+                status: market.status.clone(),
+            },
+        );
+    }
+
+    /// Whether this chain keeps the registry and the chain hosting `market_id` really sent
+    /// the announcement.
+    ///
+    /// Announcements failing this check are ignored rather than rejected, so a
+    /// misconfigured chain cannot block the registry chain's inbox.
+    fn accepts_announcement(&self, context: &linera_sdk::base::ContractRuntimeContext, market_id: MarketId) -> bool {
+        self.parameters.registry_chain == Some(context.chain_id())
+            && context.message_origin_chain() == Some(market_id.chain_id)
+    }
+
+    /// Add a market announced by another chain to the registry
+    async fn register_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        summary: MarketSummary,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        if !self.accepts_announcement(context, summary.market_id) {
+            return Ok(vec![]);
+        }
+        self.state.registry.insert(&summary.market_id, summary)?;
+        Ok(vec![])
+    }
+
+    /// Record the new status of a registered market
+    async fn update_registered_market(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market_id: MarketId,
+        status: MarketStatus,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        if !self.accepts_announcement(context, market_id) {
+            return Ok(vec![]);
+        }
+        if let Some(mut summary) = self.state.registry.get(&market_id).await? {
+            summary.status = status;
+            self.state.registry.insert(&market_id, summary)?;
+        }
+        Ok(vec![])
+    }
+
+    /// Whether `owner` is the application-wide admin or an admin of this chain
+    async fn is_admin(&self, owner: Owner) -> Result<bool, PredictionMarketError> {
+        Ok(self.parameters.admin == Some(owner) || self.state.admins.contains(&owner).await?)
+    }
+
+    /// Return the signer if they are an admin, and fail otherwise
+    async fn check_admin(
+        &self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+    ) -> Result<Owner, PredictionMarketError> {
+        let signer = context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)?;
+        if !self.is_admin(signer).await? {
+            return Err(PredictionMarketError::NotAdmin);
+        }
+        Ok(signer)
+    }
+
+    /// Return the bonds posted on a proposed or disputed answer of `market`
+    fn return_dispute_bonds(
+        context: &linera_sdk::base::ContractRuntimeContext,
+        market: &Market,
+    ) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+        let mut effects = Vec::new();
+        if let (Some(settings), Some(proposal)) = (&market.dispute_settings, &market.proposal) {
+            for owner in std::iter::once(proposal.proposer).chain(proposal.disputer) {
+                Self::transfer_from_escrow(context, owner, settings.bond)?;
+                effects.push(PredictionMarketEffect::BondReturned {
+                    market_id: market.id,
+                    owner,
+                    amount: settings.bond,
+                });
+            }
+        }
+        Ok(effects)
+    }
+
+    /// Load a market by ID
+    async fn load_market(&self, market_id: MarketId) -> Result<Market, PredictionMarketError> {
+        self.state
+            .markets
+            .get(&market_id)
+            .await?
+            .ok_or(PredictionMarketError::MarketNotFound(market_id))
+    }
+
+    /// The account holding all escrowed stakes: the application's own balance on this chain
+    fn escrow_account(context: &linera_sdk::base::ContractRuntimeContext) -> Account {
+        Account {
+            chain_id: context.chain_id(),
+            owner: Some(AccountOwner::Application(context.application_id())),
+        }
+    }
+
+    /// Debit `amount` from `user` and credit it to the application escrow
+    fn transfer_to_escrow(
+        context: &linera_sdk::base::ContractRuntimeContext,
+        user: Owner,
+        amount: Amount,
+    ) -> Result<(), PredictionMarketError> {
+        let escrow = Self::escrow_account(context);
+        context
+            .transfer(Some(AccountOwner::User(user)), escrow, amount)
+            .map_err(|e| PredictionMarketError::TransferFailed {
+                amount,
+                reason: e.to_string(),
+            })
+    }
+
+    /// Pay `amount` out of the application escrow to `user`
+    fn transfer_from_escrow(
+        context: &linera_sdk::base::ContractRuntimeContext,
+        user: Owner,
+        amount: Amount,
+    ) -> Result<(), PredictionMarketError> {
+        let destination = Account {
+            chain_id: context.chain_id(),
+            owner: Some(AccountOwner::User(user)),
+        };
+        Self::transfer_from_escrow_to(context, destination, amount)
+    }
+
+    /// Pay `amount` from the application escrow to the holder of `position`, on the chain
+    /// its bets were placed from
+    fn pay_bettor(
+        context: &linera_sdk::base::ContractRuntimeContext,
+        position: &Position,
+        amount: Amount,
+    ) -> Result<(), PredictionMarketError> {
+        let destination = Account {
+            chain_id: position.origin,
+            owner: Some(AccountOwner::User(position.user)),
+        };
+        Self::transfer_from_escrow_to(context, destination, amount)
+    }
+
+    /// Transfer `amount` from the application escrow to `destination`
+    fn transfer_from_escrow_to(
+        context: &linera_sdk::base::ContractRuntimeContext,
+        destination: Account,
+        amount: Amount,
+    ) -> Result<(), PredictionMarketError> {
+        context
+            .transfer(Some(AccountOwner::Application(context.application_id())), destination, amount)
+            .map_err(|e| PredictionMarketError::TransferFailed {
+                amount,
+                reason: e.to_string(),
+            })
+    }
+}
+
+/// Owner who signed the operation being executed
+fn signer(context: &linera_sdk::base::ContractRuntimeContext) -> Result<Owner, PredictionMarketError> {
+    context.authenticated_signer().ok_or(PredictionMarketError::Unauthorized)
+}
+
+/// Fail unless a remote request on `position` comes from the chain its bets were placed
+/// from. Operations on this chain are signed by the user and need no such check.
+fn check_origin(
+    context: &linera_sdk::base::ContractRuntimeContext,
+    position: &Position,
+) -> Result<(), PredictionMarketError> {
+    match context.message_origin_chain() {
+        Some(origin) if origin != position.origin => Err(PredictionMarketError::WrongOrigin),
+        _ => Ok(()),
+    }
+}
+
+/// Turn the outcome of a remote withdrawal, vote or claim into the effects of the message.
+///
+/// A refused request is answered with `RemoteRequestRejected` instead of failing, so a bad
+/// request cannot block the market chain's inbox. Storage errors still fail the message.
+fn answer_remote_request(
+    context: &linera_sdk::base::ContractRuntimeContext,
+    market_id: MarketId,
+    user: Owner,
+    result: Result<Vec<PredictionMarketEffect>, PredictionMarketError>,
+) -> Result<Vec<PredictionMarketEffect>, PredictionMarketError> {
+    match result {
+        Err(PredictionMarketError::Storage(reason)) => Err(PredictionMarketError::Storage(reason)),
+        Err(error) => {
+            if let Some(origin) = context.message_origin_chain() {
+                context.send_effect(
+                    origin,
+                    PredictionMarketEffect::RemoteRequestRejected {
+                        market_id,
+                        user,
+                        code: error.code().to_string(),
+                    },
+                );
+            }
+            Ok(vec![])
+        }
+        effects => effects,
+    }
+}
+
+/// Fail unless `market` can still be resolved at `now`: it must not be settled already,
+/// and its resolution deadline, if any, must not have passed
+fn check_unresolved(market: &Market, now: Timestamp) -> Result<(), PredictionMarketError> {
+    match market.status {
+        MarketStatus::Resolved | MarketStatus::ResolvedNoWinners => Err(PredictionMarketError::AlreadyResolved),
+        MarketStatus::Cancelled => Err(PredictionMarketError::MarketCancelled),
+        MarketStatus::Expired => Err(PredictionMarketError::MarketExpired),
+        _ if market.resolution_deadline.is_some_and(|deadline| now >= deadline) => {
+            Err(PredictionMarketError::ResolutionDeadlinePassed)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Add `minutes` to `timestamp`, failing on overflow
+fn add_minutes(timestamp: Timestamp, minutes: u64) -> Result<Timestamp, PredictionMarketError> {
+    let millis = minutes
+        .checked_mul(60 * 1000) // Convert minutes to milliseconds
+        .ok_or(PredictionMarketError::ArithmeticOverflow)?;
+    timestamp
+        .saturating_sub(Timestamp::from(0))
+        .checked_add(millis)
+        .map(Timestamp::from)
+        .ok_or(PredictionMarketError::ArithmeticOverflow)
 }
 
 #[async_trait::async_trait]
@@ -477,16 +2119,30 @@ impl Service for PredictionMarket {
 
     async fn handle_query(
         &self,
-        _context: &linera_sdk::base::ServiceRuntimeContext,
+        context: &linera_sdk::base::ServiceRuntimeContext,
         query: Self::Query,
     ) -> Result<Self::QueryResponse, linera_sdk::base::ContractError> {
-        // Handle queries (e.g., get market details)
-        // This will be implemented in the GraphQL service
-        // For now, return serialized state
-        match serde_json::to_string(&self.state) {
+        // An empty query asks for the snapshot of the whole state
+        let query = if query.is_empty() {
+            PredictionMarketQuery::Snapshot
+        } else {
+            serde_json::from_str(&query)
+                .map_err(|e| linera_sdk::base::ContractError::Other(format!("Invalid query: {}", e)))?
+        };
+        let response = match query {
+            PredictionMarketQuery::Snapshot => {
+                let mut snapshot = self.state.snapshot(context.system_time()).await.map_err(PredictionMarketError::from)?;
+                snapshot.parameters = self.parameters.clone();
+                serde_json::to_string(&snapshot)
+            }
+            PredictionMarketQuery::RegistryPage { offset, limit } => {
+                let page = self.state.registry_page(offset, limit).await.map_err(PredictionMarketError::from)?;
+                serde_json::to_string(&page)
+            }
+        };
+        match response {
             Ok(json) => Ok(json),
             Err(e) => Err(linera_sdk::base::ContractError::Other(format!("Serialization error: {}", e))),
         }
     }
 }
-
@@ -0,0 +1,398 @@
+//! Versioned state layouts and migrations
+//!
+//! `PredictionMarketState::state_version` records the layout of the stored state. When the
+//! contract loads a state older than `STATE_VERSION`, `migrate` upgrades it one version at
+//! a time before any operation runs, and `store` saves the upgraded records. A state newer
+//! than the contract is rejected rather than misread.
+//!
+//! To change a stored type: freeze its current definition here as `<Type>V<n>`, bump
+//! `STATE_VERSION`, and add a step reading the version `n` records with the old types.
+//!
+//! Versions:
+//! - 0: the deployed contract before views. The whole state is one serialized
+//!   `StateV0` value under the application's base key, which no view field uses: markets
+//!   indexed by their local `u64` ID and one bet per `(market, owner)`.
+//! - 1: current layout, a root view with one key per market and position.
+
+use crate::{
+    Bet, Market, MarketId, MarketStatus, NoWinnerPolicy, Position, PredictionMarketError, PredictionMarketState,
+    Resolver, SurplusPolicy,
+};
+use linera_sdk::base::{Amount, ChainId, Owner, Timestamp};
+use linera_sdk::views::{Context, ViewStorageContext};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Layout version written by this contract
+pub const STATE_VERSION: u32 = 1;
+
+/// The state as stored in version 0
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct StateV0 {
+    /// Next market ID (auto-incrementing)
+    next_market_id: u64,
+    /// Markets indexed by ID
+    markets: BTreeMap<u64, MarketV0>,
+    /// User bets indexed by (market_id, owner)
+    user_bets: BTreeMap<(u64, Owner), BetV0>,
+}
+
+/// A market as stored in version 0
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct MarketV0 {
+    /// Unique market ID
+    id: u64,
+    /// Creator of the market
+    creator: Owner,
+    /// Market question
+    question: String,
+    /// Market description
+    description: String,
+    /// End time (timestamp in milliseconds)
+    end_time: Timestamp,
+    /// Market status
+    status: MarketStatusV0,
+    /// Available options
+    options: Vec<String>,
+    /// Correct answer (set when resolved)
+    correct_answer: Option<String>,
+    /// Total bets per option
+    bets: BTreeMap<String, Amount>,
+    /// Total pool amount
+    total_pool: Amount,
+    /// Maximum reward per winner (in native tokens)
+    max_reward: Amount,
+    /// Creation timestamp
+    created_at: Timestamp,
+}
+
+/// Market status as stored in version 0
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+enum MarketStatusV0 {
+    Active,
+    Locked,
+    Resolved,
+}
+
+/// A user bet as stored in version 0; each user had at most one bet per market
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct BetV0 {
+    /// Market ID
+    market_id: u64,
+    /// User who placed the bet
+    user: Owner,
+    /// Selected option
+    option: String,
+    /// Bet amount
+    amount: Amount,
+    /// Timestamp when bet was placed
+    timestamp: Timestamp,
+    /// Whether reward has been claimed
+    claimed: bool,
+    /// Reward amount (calculated on resolution)
+    reward_amount: Amount,
+}
+
+impl From<MarketStatusV0> for MarketStatus {
+    fn from(status: MarketStatusV0) -> Self {
+        match status {
+            MarketStatusV0::Active => MarketStatus::Active,
+            MarketStatusV0::Locked => MarketStatus::Locked,
+            MarketStatusV0::Resolved => MarketStatus::Resolved,
+        }
+    }
+}
+
+impl MarketV0 {
+    /// Convert to the current layout. Every setting added since keeps its default: the
+    /// creator resolves, nothing is escrowed as bond and no fee is charged.
+    fn upgrade(self, chain_id: ChainId) -> Market {
+        Market {
+            id: MarketId::new(chain_id, self.id),
+            creator: self.creator,
+            question: self.question,
+            description: self.description,
+            resolution_criteria: String::new(),
+            resolution_source: String::new(),
+            resolution_evidence: None,
+            end_time: self.end_time,
+            lock_before_end: None,
+            status: self.status.into(),
+            options: self.options,
+            correct_answer: self.correct_answer,
+            bets: self.bets,
+            total_pool: self.total_pool,
+            max_reward: self.max_reward,
+            no_winner_policy: NoWinnerPolicy::RefundBettors,
+            surplus_policy: SurplusPolicy::RedistributeToWinners,
+            surplus: Amount::ZERO,
+            dust: Amount::ZERO,
+            creator_bond: Amount::ZERO,
+            betting_paused: false,
+            withdrawal_fee_bps: 0,
+            withdrawal_fees: Amount::ZERO,
+            protocol_fee_bps: 0,
+            creator_fee_bps: 0,
+            protocol_fee: Amount::ZERO,
+            creator_fee: Amount::ZERO,
+            resolver: Resolver::Creator,
+            resolution_votes: BTreeMap::new(),
+            dispute_settings: None,
+            proposal: None,
+            vote_tally: BTreeMap::new(),
+            resolution_deadline: None,
+            created_at: self.created_at,
+        }
+    }
+}
+
+impl BetV0 {
+    /// Convert to a position holding this single bet
+    fn upgrade(self, chain_id: ChainId) -> Position {
+        let market_id = MarketId::new(chain_id, self.market_id);
+        let mut position = Position::new(market_id, self.user, chain_id);
+        position.stakes.insert(self.option.clone(), self.amount);
+        position.bets.push(Bet {
+            market_id,
+            user: self.user,
+            option: self.option,
+            amount: self.amount,
+            timestamp: self.timestamp,
+        });
+        position.claimed = self.claimed;
+        position.reward_amount = self.reward_amount;
+        position
+    }
+}
+
+/// Bring `state` up to `STATE_VERSION` and return the version it was stored with.
+///
+/// Markets of version 0 states get their IDs on `chain_id`, the chain hosting them.
+pub async fn migrate(
+    state: &mut PredictionMarketState,
+    context: ViewStorageContext,
+    chain_id: ChainId,
+) -> Result<u32, PredictionMarketError> {
+    let stored = *state.state_version.get();
+    if stored > STATE_VERSION {
+        return Err(PredictionMarketError::UnsupportedStateVersion(stored));
+    }
+    if stored == 0 {
+        migrate_v0(state, context, chain_id).await?;
+    }
+    state.state_version.set(STATE_VERSION);
+    Ok(stored)
+}
+
+/// Rewrite the serialized version 0 state, if any, as view entries
+async fn migrate_v0(
+    state: &mut PredictionMarketState,
+    context: ViewStorageContext,
+    chain_id: ChainId,
+) -> Result<(), PredictionMarketError> {
+    // Chains instantiated after versioning have no legacy value
+    let Some(legacy) = context.read_value::<StateV0>(&context.base_key()).await? else {
+        return Ok(());
+    };
+
+    state.next_market_id.set(legacy.next_market_id);
+    for (_, market) in legacy.markets {
+        let market = market.upgrade(chain_id);
+        state.markets.insert(&market.id, market)?;
+    }
+
+    // Index the bettors of each market alongside their positions
+    let mut bettors: BTreeMap<MarketId, Vec<Owner>> = BTreeMap::new();
+    for (_, bet) in legacy.user_bets {
+        let position = bet.upgrade(chain_id);
+        bettors.entry(position.market_id).or_default().push(position.user);
+        state.positions.insert(&(position.market_id, position.user), position)?;
+    }
+    for (market_id, users) in bettors {
+        state.bettors.insert(&market_id, users)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use linera_sdk::base::CryptoHash;
+    use linera_sdk::contract::MockContractRuntime;
+    use linera_sdk::views::{Batch, RootView, View};
+
+    fn storage() -> ViewStorageContext {
+        ViewStorageContext::new_unsafe(MockContractRuntime::new().key_value_store(), Vec::new(), ())
+    }
+
+    fn owner(name: &str) -> Owner {
+        Owner::from(CryptoHash::test_hash(name))
+    }
+
+    /// Write `legacy` the way the contract stored its state before views
+    fn write_legacy(context: &ViewStorageContext, legacy: &StateV0) {
+        let mut batch = Batch::new();
+        batch.put_key_value(context.base_key(), legacy).unwrap();
+        context.write_batch(batch).now_or_never().unwrap().unwrap();
+    }
+
+    fn bet_v0(market_id: u64, user: Owner, option: &str, tokens: u128, reward: u128, claimed: bool) -> BetV0 {
+        BetV0 {
+            market_id,
+            user,
+            option: option.to_string(),
+            amount: Amount::from_tokens(tokens),
+            timestamp: Timestamp::from(1_500),
+            claimed,
+            reward_amount: Amount::from_tokens(reward),
+        }
+    }
+
+    /// A deployed state: one resolved market with a claimed and an unclaimed winner and a
+    /// loser, and one active market
+    fn state_v0() -> StateV0 {
+        let resolved = MarketV0 {
+            id: 1,
+            creator: owner("creator"),
+            question: "Will it rain?".to_string(),
+            description: "At the station".to_string(),
+            end_time: Timestamp::from(2_000),
+            status: MarketStatusV0::Resolved,
+            options: vec!["Yes".to_string(), "No".to_string()],
+            correct_answer: Some("Yes".to_string()),
+            bets: BTreeMap::from([
+                ("Yes".to_string(), Amount::from_tokens(4)),
+                ("No".to_string(), Amount::from_tokens(6)),
+            ]),
+            total_pool: Amount::from_tokens(10),
+            max_reward: Amount::from_tokens(100),
+            created_at: Timestamp::from(1_000),
+        };
+        let active = MarketV0 {
+            id: 2,
+            question: "Will it snow?".to_string(),
+            status: MarketStatusV0::Active,
+            correct_answer: None,
+            bets: BTreeMap::from([("No".to_string(), Amount::from_tokens(1))]),
+            total_pool: Amount::from_tokens(1),
+            end_time: Timestamp::from(9_000),
+            ..resolved.clone()
+        };
+        StateV0 {
+            next_market_id: 3,
+            markets: BTreeMap::from([(1, resolved), (2, active)]),
+            user_bets: BTreeMap::from([
+                ((1, owner("alice")), bet_v0(1, owner("alice"), "Yes", 1, 2, true)),
+                ((1, owner("bob")), bet_v0(1, owner("bob"), "Yes", 3, 7, false)),
+                ((1, owner("carol")), bet_v0(1, owner("carol"), "No", 6, 0, false)),
+                ((2, owner("alice")), bet_v0(2, owner("alice"), "No", 1, 0, false)),
+            ]),
+        }
+    }
+
+    #[test]
+    fn migrates_version_0_states() {
+        let context = storage();
+        let chain_id = ChainId::root(0);
+        write_legacy(&context, &state_v0());
+
+        let mut state = PredictionMarketState::load(context.clone()).now_or_never().unwrap().unwrap();
+        let stored = migrate(&mut state, context, chain_id).now_or_never().unwrap();
+        assert_eq!(stored, Ok(0));
+        assert_eq!(*state.state_version.get(), STATE_VERSION);
+        assert_eq!(state.peek_market_id(), 3);
+
+        let resolved_id = MarketId::new(chain_id, 1);
+        let market = state.markets.get(&resolved_id).now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(market.id, resolved_id);
+        assert_eq!(market.creator, owner("creator"));
+        assert_eq!(market.question, "Will it rain?");
+        assert_eq!(market.description, "At the station");
+        assert_eq!(market.status, MarketStatus::Resolved);
+        assert_eq!(market.correct_answer.as_deref(), Some("Yes"));
+        assert_eq!(market.bets.get("No"), Some(&Amount::from_tokens(6)));
+        assert_eq!(market.total_pool, Amount::from_tokens(10));
+        assert_eq!(market.max_reward, Amount::from_tokens(100));
+        assert_eq!(market.end_time, Timestamp::from(2_000));
+        assert_eq!(market.created_at, Timestamp::from(1_000));
+        assert_eq!(market.creator_bond, Amount::ZERO);
+        assert_eq!(market.resolver, Resolver::Creator);
+
+        let active_id = MarketId::new(chain_id, 2);
+        let market = state.markets.get(&active_id).now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(market.status, MarketStatus::Active);
+        assert_eq!(market.correct_answer, None);
+
+        let position = state
+            .positions
+            .get(&(resolved_id, owner("alice")))
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(position.origin, chain_id);
+        assert_eq!(position.stake_on("Yes"), Amount::from_tokens(1));
+        assert_eq!(position.bets.len(), 1);
+        assert_eq!(position.bets[0].timestamp, Timestamp::from(1_500));
+        assert!(position.claimed);
+        assert_eq!(position.reward_amount, Amount::from_tokens(2));
+
+        let position = state
+            .positions
+            .get(&(resolved_id, owner("bob")))
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(!position.claimed);
+        assert_eq!(position.reward_amount, Amount::from_tokens(7));
+
+        let bettors = state.bettors.get(&resolved_id).now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(bettors.len(), 3);
+        for user in ["alice", "bob", "carol"] {
+            assert!(bettors.contains(&owner(user)));
+        }
+        let bettors = state.bettors.get(&active_id).now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(bettors, vec![owner("alice")]);
+    }
+
+    #[test]
+    fn versions_fresh_states() {
+        let context = storage();
+        let mut state = PredictionMarketState::load(context.clone()).now_or_never().unwrap().unwrap();
+        let stored = migrate(&mut state, context, ChainId::root(0)).now_or_never().unwrap();
+        assert_eq!(stored, Ok(0));
+        assert_eq!(*state.state_version.get(), STATE_VERSION);
+        assert_eq!(state.peek_market_id(), 1);
+    }
+
+    #[test]
+    fn keeps_current_states() {
+        let context = storage();
+        let mut state = PredictionMarketState::load(context.clone()).now_or_never().unwrap().unwrap();
+        state.state_version.set(STATE_VERSION);
+        state.save().now_or_never().unwrap().unwrap();
+
+        // A leftover legacy value is not read again once the state is versioned
+        write_legacy(&context, &state_v0());
+        let mut state = PredictionMarketState::load(context.clone()).now_or_never().unwrap().unwrap();
+        let stored = migrate(&mut state, context, ChainId::root(0)).now_or_never().unwrap();
+        assert_eq!(stored, Ok(STATE_VERSION));
+        assert_eq!(state.peek_market_id(), 1);
+    }
+
+    #[test]
+    fn rejects_newer_states() {
+        let context = storage();
+        let mut state = PredictionMarketState::load(context.clone()).now_or_never().unwrap().unwrap();
+        state.state_version.set(STATE_VERSION + 1);
+        state.save().now_or_never().unwrap().unwrap();
+
+        let mut state = PredictionMarketState::load(context.clone()).now_or_never().unwrap().unwrap();
+        assert_eq!(
+            migrate(&mut state, context, ChainId::root(0)).now_or_never().unwrap(),
+            Err(PredictionMarketError::UnsupportedStateVersion(STATE_VERSION + 1))
+        );
+    }
+}
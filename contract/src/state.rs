@@ -0,0 +1,121 @@
+//! On-chain state of the prediction market application
+//!
+//! The state is a root view: each entry of `markets` and `positions` is stored under its
+//! own key, so an operation only loads and saves the markets and positions it touches.
+//! The stored types themselves are defined in `prediction-market-abi`.
+
+use crate::{
+    FeeBalance, Market, MarketId, MarketSummary, OutgoingBet, Position, PredictionMarketSnapshot, RegistryPage,
+    Vote,
+};
+use linera_sdk::base::{Amount, Owner, Timestamp};
+use linera_sdk::views::{MapView, RegisterView, RootView, SetView, ViewError, ViewStorageContext};
+
+/// The state of the prediction market application.
+///
+/// Each field is stored under a key prefix derived from its position, so new fields are
+/// only ever appended; changing a stored type requires a new `STATE_VERSION` and a step
+/// in `migration`.
+#[derive(RootView)]
+#[view(context = "ViewStorageContext")]
+pub struct PredictionMarketState {
+    /// Index of the next market created on this chain (auto-incrementing, starts at 1)
+    pub next_market_id: RegisterView<u64>,
+    /// Markets hosted on this chain indexed by ID
+    pub markets: MapView<MarketId, Market>,
+    /// User positions indexed by (market_id, owner)
+    pub positions: MapView<(MarketId, Owner), Position>,
+    /// Users holding a position on each market, so resolution only loads that market's positions
+    pub bettors: MapView<MarketId, Vec<Owner>>,
+    /// Owners allowed to pause the chain, cancel any unresolved market and manage admins
+    pub admins: SetView<Owner>,
+    /// Bets sent from this chain to markets on other chains, awaiting acknowledgement,
+    /// indexed by (market_id, owner) in the order they were sent
+    pub outgoing_bets: MapView<(MarketId, Owner), Vec<OutgoingBet>>,
+    /// Global market index, only filled on the registry chain
+    pub registry: MapView<MarketId, MarketSummary>,
+    /// Bettor votes on markets resolved by vote, indexed by (market_id, voter)
+    pub votes: MapView<(MarketId, Owner), Vote>,
+    /// Protocol and creator fees accrued per recipient and not yet withdrawn
+    pub fee_balances: MapView<Owner, Amount>,
+    /// Whether admins paused market creation on this chain
+    pub creation_paused: RegisterView<bool>,
+    /// Whether admins paused betting on every market of this chain
+    pub betting_paused: RegisterView<bool>,
+    /// Layout version of the stored state; 0 on states written before versioning
+    pub state_version: RegisterView<u32>,
+}
+
+impl PredictionMarketState {
+    /// Return the index of the next market, defaulting to 1 on a fresh state
+    pub fn peek_market_id(&self) -> u64 {
+        (*self.next_market_id.get()).max(1)
+    }
+
+    /// Load every market and position into a `PredictionMarketSnapshot`.
+    ///
+    /// Market statuses are reported as of `now`, so expired markets show as `Locked`
+    /// even if no operation has touched them since their lock time.
+    pub async fn snapshot(&self, now: Timestamp) -> Result<PredictionMarketSnapshot, ViewError> {
+        let mut snapshot = PredictionMarketSnapshot {
+            next_market_id: self.peek_market_id(),
+            admins: self.admins.indices().await?,
+            creation_paused: *self.creation_paused.get(),
+            betting_paused: *self.betting_paused.get(),
+            ..PredictionMarketSnapshot::default()
+        };
+        self.markets
+            .for_each_index_value(|id, mut market| {
+                market.refresh_status(now);
+                snapshot.markets.insert(id, market);
+                Ok(())
+            })
+            .await?;
+        self.positions
+            .for_each_index_value(|_, position| {
+                snapshot.positions.push(position);
+                Ok(())
+            })
+            .await?;
+        self.outgoing_bets
+            .for_each_index_value(|_, bets| {
+                snapshot.outgoing_bets.extend(bets);
+                Ok(())
+            })
+            .await?;
+        self.votes
+            .for_each_index_value(|_, vote| {
+                snapshot.votes.push(vote);
+                Ok(())
+            })
+            .await?;
+        self.fee_balances
+            .for_each_index_value(|owner, amount| {
+                snapshot.fee_balances.push(FeeBalance { owner, amount });
+                Ok(())
+            })
+            .await?;
+        Ok(snapshot)
+    }
+
+    /// Read `limit` entries of the global market index, skipping the first `offset`.
+    ///
+    /// Only the entries up to the end of the page are loaded.
+    pub async fn registry_page(&self, offset: u64, limit: u64) -> Result<RegistryPage, ViewError> {
+        let mut page = RegistryPage {
+            total: self.registry.count().await? as u64,
+            ..RegistryPage::default()
+        };
+        let mut position = 0;
+        self.registry
+            .for_each_index_value_while(|_, summary| {
+                if position >= offset && (page.items.len() as u64) < limit {
+                    page.items.push(summary);
+                }
+                position += 1;
+                Ok((page.items.len() as u64) < limit)
+            })
+            .await?;
+        Ok(page)
+    }
+}
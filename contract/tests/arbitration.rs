@@ -0,0 +1,172 @@
+//! Optimistic resolution of disputed markets, ruled on by the arbiter
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{add_block_as, add_users, balance, escrow_balance, snapshot, try_add_block_as};
+use linera_sdk::base::{AccountOwner, Amount, CryptoHash, Owner, TimeDelta};
+use linera_sdk::test::TestValidator;
+use prediction_market::{
+    DisputeSettings, Evidence, MarketId, MarketStatus, NoWinnerPolicy, PredictionMarketAbi,
+    PredictionMarketInstantiationArgument, PredictionMarketMessage, PredictionMarketParameters, Resolver,
+    SurplusPolicy,
+};
+
+/// A yes/no market resolved by its creator, with a ten-minute challenge window
+fn disputed_market(bond: Amount, arbiter: Owner) -> PredictionMarketMessage {
+    PredictionMarketMessage::CreateMarket {
+        question: "Will it rain?".to_string(),
+        description: String::new(),
+        resolution_criteria: "Rain recorded at the station".to_string(),
+        resolution_source: "Weather station".to_string(),
+        duration_minutes: 1,
+        options: vec!["Yes".to_string(), "No".to_string()],
+        max_reward: Amount::from_tokens(1_000),
+        lock_before_end: None,
+        no_winner_policy: NoWinnerPolicy::RefundBettors,
+        surplus_policy: SurplusPolicy::RedistributeToWinners,
+        withdrawal_fee_bps: 0,
+        creator_fee_bps: 0,
+        resolver: Resolver::Creator,
+        dispute_settings: Some(DisputeSettings {
+            challenge_minutes: 10,
+            bond,
+            arbiter,
+        }),
+        resolution_deadline_minutes: None,
+    }
+}
+
+fn evidence(source: &str) -> Evidence {
+    Evidence {
+        source: source.to_string(),
+        content_hash: CryptoHash::test_hash(source),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn arbiter_rulings_move_the_bonds() {
+    let (validator, bytecode) = TestValidator::with_current_bytecode::<
+        PredictionMarketAbi,
+        PredictionMarketParameters,
+        PredictionMarketInstantiationArgument,
+    >()
+    .await;
+    let mut chain = validator.new_chain().await;
+    let market_app = chain
+        .create_application(
+            bytecode,
+            PredictionMarketParameters::default(),
+            PredictionMarketInstantiationArgument::default(),
+            vec![],
+        )
+        .await;
+
+    let funds = Amount::from_tokens(10);
+    let bond = Amount::from_tokens(2);
+    let users = add_users(&chain, 3, funds).await;
+    let (creator, disputer, arbiter) = (&users[0], &users[1], &users[2]);
+
+    // The creator cannot arbitrate their own market
+    let accepted = try_add_block_as(&mut chain, creator, |block| {
+        block.with_operation(market_app, disputed_market(bond, creator.owner));
+    })
+    .await;
+    assert!(!accepted);
+
+    // One ruling upholds the proposed answer, the other overturns it
+    add_block_as(&mut chain, creator, |block| {
+        block
+            .with_operation(market_app, disputed_market(bond, arbiter.owner))
+            .with_operation(market_app, disputed_market(bond, arbiter.owner));
+    })
+    .await;
+    let upheld = MarketId::new(chain.id(), 1);
+    let overturned = MarketId::new(chain.id(), 2);
+
+    // The creator proposes "Yes" on both markets and the disputer challenges both
+    validator.clock().add(TimeDelta::from_secs(120));
+    add_block_as(&mut chain, creator, |block| {
+        for market_id in [upheld, overturned] {
+            block.with_operation(
+                market_app,
+                PredictionMarketMessage::ResolveMarket {
+                    market_id,
+                    correct_answer: "Yes".to_string(),
+                    evidence: evidence("Station log"),
+                },
+            );
+        }
+    })
+    .await;
+    add_block_as(&mut chain, disputer, |block| {
+        for market_id in [upheld, overturned] {
+            block.with_operation(market_app, PredictionMarketMessage::DisputeResolution { market_id });
+        }
+    })
+    .await;
+    let double_bond = bond.saturating_add(bond);
+    let after_bonds = funds.saturating_sub(double_bond);
+    assert_eq!(balance(&chain, AccountOwner::User(creator.owner)).await, after_bonds);
+    assert_eq!(balance(&chain, AccountOwner::User(disputer.owner)).await, after_bonds);
+    assert_eq!(escrow_balance(&chain, market_app).await, double_bond.saturating_add(double_bond));
+
+    // Only the arbiter can rule
+    let accepted = try_add_block_as(&mut chain, disputer, |block| {
+        block.with_operation(
+            market_app,
+            PredictionMarketMessage::ArbitrateMarket {
+                market_id: overturned,
+                correct_answer: "No".to_string(),
+                evidence: evidence("Radar archive"),
+            },
+        );
+    })
+    .await;
+    assert!(!accepted);
+
+    // Upholding the answer pays both of its bonds to the creator
+    add_block_as(&mut chain, arbiter, |block| {
+        block.with_operation(
+            market_app,
+            PredictionMarketMessage::ArbitrateMarket {
+                market_id: upheld,
+                correct_answer: "Yes".to_string(),
+                evidence: evidence("Station log"),
+            },
+        );
+    })
+    .await;
+    assert_eq!(balance(&chain, AccountOwner::User(creator.owner)).await, funds);
+    assert_eq!(balance(&chain, AccountOwner::User(disputer.owner)).await, after_bonds);
+    assert_eq!(escrow_balance(&chain, market_app).await, double_bond);
+
+    // Overturning the answer pays both of its bonds to the disputer and keeps the arbiter's evidence
+    add_block_as(&mut chain, arbiter, |block| {
+        block.with_operation(
+            market_app,
+            PredictionMarketMessage::ArbitrateMarket {
+                market_id: overturned,
+                correct_answer: "No".to_string(),
+                evidence: evidence("Radar archive"),
+            },
+        );
+    })
+    .await;
+    assert_eq!(balance(&chain, AccountOwner::User(creator.owner)).await, funds);
+    assert_eq!(balance(&chain, AccountOwner::User(disputer.owner)).await, funds);
+    assert_eq!(balance(&chain, AccountOwner::User(arbiter.owner)).await, funds);
+    assert_eq!(escrow_balance(&chain, market_app).await, Amount::ZERO);
+
+    // Nobody bet, so both markets resolve without winners on the arbiter's answer
+    let state = snapshot(&chain, market_app).await;
+    let market = &state.markets[&upheld];
+    assert_eq!(market.status, MarketStatus::ResolvedNoWinners);
+    assert_eq!(market.correct_answer.as_deref(), Some("Yes"));
+    let market = &state.markets[&overturned];
+    assert_eq!(market.status, MarketStatus::ResolvedNoWinners);
+    assert_eq!(market.correct_answer.as_deref(), Some("No"));
+    let source = market.resolution_evidence.as_ref().map(|evidence| evidence.source.as_str());
+    assert_eq!(source, Some("Radar archive"));
+}
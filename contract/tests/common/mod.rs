@@ -0,0 +1,78 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use linera_sdk::base::{Account, AccountOwner, Amount, ApplicationId, KeyPair, Owner, TimeoutConfig};
+use linera_sdk::test::{ActiveChain, BlockBuilder, Recipient};
+use prediction_market::{PredictionMarketAbi, PredictionMarketSnapshot};
+
+/// An owner of a test chain, signing their own blocks
+pub struct User {
+    pub key_pair: KeyPair,
+    pub owner: Owner,
+}
+
+/// Make `count` new users owners of `chain`, next to its current owner, and fund each of
+/// their accounts on `chain` with `funds` from the chain balance
+pub async fn add_users(chain: &ActiveChain, count: usize, funds: Amount) -> Vec<User> {
+    let users: Vec<User> = (0..count)
+        .map(|_| {
+            let key_pair = KeyPair::generate();
+            let owner = Owner::from(key_pair.public());
+            User { key_pair, owner }
+        })
+        .collect();
+    let chain_id = chain.id();
+    let owners = std::iter::once(chain.public_key())
+        .chain(users.iter().map(|user| user.key_pair.public()))
+        .map(|public_key| (public_key, 100))
+        .collect();
+    chain
+        .add_block(|block| {
+            block.with_owner_change(Vec::new(), owners, 0, false, TimeoutConfig::default());
+            for user in &users {
+                let account = Account {
+                    chain_id,
+                    owner: Some(AccountOwner::User(user.owner)),
+                };
+                block.with_native_token_transfer(None, Recipient::Account(account), funds);
+            }
+        })
+        .await;
+    users
+}
+
+/// Add a block to `chain` signed by `user`
+pub async fn add_block_as(chain: &mut ActiveChain, user: &User, block_builder: impl FnOnce(&mut BlockBuilder)) {
+    chain.set_key_pair(user.key_pair.copy());
+    chain.add_block(block_builder).await;
+}
+
+/// Try to add a block to `chain` signed by `user`, returning whether it was accepted
+pub async fn try_add_block_as(
+    chain: &mut ActiveChain,
+    user: &User,
+    block_builder: impl FnOnce(&mut BlockBuilder),
+) -> bool {
+    chain.set_key_pair(user.key_pair.copy());
+    chain.try_add_block(block_builder).await.is_ok()
+}
+
+/// Balance of `owner`'s account on `chain`
+pub async fn balance(chain: &ActiveChain, owner: AccountOwner) -> Amount {
+    chain.owner_balance(&owner).await.unwrap_or(Amount::ZERO)
+}
+
+/// Balance of the application escrow on `chain`
+pub async fn escrow_balance(chain: &ActiveChain, application_id: ApplicationId<PredictionMarketAbi>) -> Amount {
+    balance(chain, AccountOwner::Application(application_id.forget_abi())).await
+}
+
+/// Read the state snapshot of the market application
+pub async fn snapshot(
+    chain: &ActiveChain,
+    application_id: ApplicationId<PredictionMarketAbi>,
+) -> PredictionMarketSnapshot {
+    let response = chain.query(application_id, String::new()).await;
+    serde_json::from_str(&response).expect("the contract answers queries with a snapshot")
+}
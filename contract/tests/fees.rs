@@ -0,0 +1,143 @@
+//! Protocol and creator fees, from resolution to withdrawal
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{add_block_as, add_users, balance, escrow_balance, snapshot, try_add_block_as};
+use linera_sdk::base::{AccountOwner, Amount, CryptoHash, TimeDelta};
+use linera_sdk::test::TestValidator;
+use prediction_market::{
+    Evidence, MarketId, MarketStatus, NoWinnerPolicy, PredictionMarketAbi, PredictionMarketInstantiationArgument,
+    PredictionMarketMessage, PredictionMarketParameters, Resolver, SurplusPolicy,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn claims_and_fee_withdrawals_empty_the_escrow() {
+    let (validator, bytecode) = TestValidator::with_current_bytecode::<
+        PredictionMarketAbi,
+        PredictionMarketParameters,
+        PredictionMarketInstantiationArgument,
+    >()
+    .await;
+    let mut chain = validator.new_chain().await;
+    let funds = Amount::from_tokens(20);
+    let users = add_users(&chain, 5, funds).await;
+    let (creator, treasury, alice, bob, carol) = (&users[0], &users[1], &users[2], &users[3], &users[4]);
+
+    // A 5% protocol fee and a 15% creator fee leave 80% of the pool to the winners
+    let parameters = PredictionMarketParameters {
+        protocol_fee_bps: 500,
+        max_creator_fee_bps: 1_500,
+        treasury: Some(treasury.owner),
+        ..PredictionMarketParameters::default()
+    };
+    let market_app = chain
+        .create_application(bytecode, parameters, PredictionMarketInstantiationArgument::default(), vec![])
+        .await;
+    add_block_as(&mut chain, creator, |block| {
+        block.with_operation(
+            market_app,
+            PredictionMarketMessage::CreateMarket {
+                question: "Will it rain?".to_string(),
+                description: String::new(),
+                resolution_criteria: "Rain recorded at the station".to_string(),
+                resolution_source: "Weather station".to_string(),
+                duration_minutes: 1,
+                options: vec!["Yes".to_string(), "No".to_string()],
+                max_reward: Amount::from_tokens(1_000),
+                lock_before_end: None,
+                no_winner_policy: NoWinnerPolicy::RefundBettors,
+                surplus_policy: SurplusPolicy::RedistributeToWinners,
+                withdrawal_fee_bps: 0,
+                creator_fee_bps: 1_500,
+                resolver: Resolver::Creator,
+                dispute_settings: None,
+                resolution_deadline_minutes: None,
+            },
+        );
+    })
+    .await;
+    let market_id = MarketId::new(chain.id(), 1);
+
+    // A pool of 20 tokens, 4 of them on the correct answer
+    for (user, option, tokens) in [(alice, "Yes", 3), (bob, "Yes", 1), (carol, "No", 16)] {
+        add_block_as(&mut chain, user, |block| {
+            block.with_operation(
+                market_app,
+                PredictionMarketMessage::PlaceBet {
+                    market_id,
+                    option: option.to_string(),
+                    amount: Amount::from_tokens(tokens),
+                },
+            );
+        })
+        .await;
+    }
+
+    validator.clock().add(TimeDelta::from_secs(120));
+    add_block_as(&mut chain, creator, |block| {
+        block.with_operation(
+            market_app,
+            PredictionMarketMessage::ResolveMarket {
+                market_id,
+                correct_answer: "Yes".to_string(),
+                evidence: Evidence {
+                    source: "Station log".to_string(),
+                    content_hash: CryptoHash::test_hash("Station log"),
+                },
+            },
+        );
+    })
+    .await;
+
+    // The fees stay in escrow, next to the rewards, until they are withdrawn
+    let state = snapshot(&chain, market_app).await;
+    let market = &state.markets[&market_id];
+    assert_eq!(market.status, MarketStatus::Resolved);
+    assert_eq!(market.protocol_fee, Amount::from_tokens(1));
+    assert_eq!(market.creator_fee, Amount::from_tokens(3));
+    assert_eq!(market.dust, Amount::ZERO);
+    assert_eq!(state.fee_balances.len(), 2);
+    assert_eq!(escrow_balance(&chain, market_app).await, Amount::from_tokens(20));
+
+    // Winners share the 16 tokens left in proportion to their stake
+    for user in [alice, bob] {
+        add_block_as(&mut chain, user, |block| {
+            block.with_operation(market_app, PredictionMarketMessage::ClaimReward { market_id });
+        })
+        .await;
+    }
+    let alice_balance = funds.saturating_sub(Amount::from_tokens(3)).saturating_add(Amount::from_tokens(12));
+    let bob_balance = funds.saturating_sub(Amount::from_tokens(1)).saturating_add(Amount::from_tokens(4));
+    assert_eq!(balance(&chain, AccountOwner::User(alice.owner)).await, alice_balance);
+    assert_eq!(balance(&chain, AccountOwner::User(bob.owner)).await, bob_balance);
+    assert_eq!(escrow_balance(&chain, market_app).await, Amount::from_tokens(4));
+
+    // Withdrawing the fees pays their recipients and empties the escrow
+    for user in [treasury, creator] {
+        add_block_as(&mut chain, user, |block| {
+            block.with_operation(market_app, PredictionMarketMessage::WithdrawFees);
+        })
+        .await;
+    }
+    assert_eq!(
+        balance(&chain, AccountOwner::User(treasury.owner)).await,
+        funds.saturating_add(Amount::from_tokens(1))
+    );
+    assert_eq!(
+        balance(&chain, AccountOwner::User(creator.owner)).await,
+        funds.saturating_add(Amount::from_tokens(3))
+    );
+    assert_eq!(escrow_balance(&chain, market_app).await, Amount::ZERO);
+
+    // Every fee balance is drained, so withdrawing again fails
+    let state = snapshot(&chain, market_app).await;
+    assert!(state.fee_balances.is_empty());
+    assert!(state.positions.iter().filter(|position| position.user != carol.owner).all(|position| position.claimed));
+    let accepted = try_add_block_as(&mut chain, treasury, |block| {
+        block.with_operation(market_app, PredictionMarketMessage::WithdrawFees);
+    })
+    .await;
+    assert!(!accepted);
+}
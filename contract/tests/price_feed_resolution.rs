@@ -0,0 +1,249 @@
+//! Resolution of price-feed markets against a local price-feed application
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{add_block_as, add_users, balance, escrow_balance, snapshot, try_add_block_as};
+use linera_sdk::base::{AccountOwner, Amount, ApplicationId, TimeDelta, Timestamp};
+use linera_sdk::test::TestValidator;
+use prediction_market::{
+    Comparator, MarketId, MarketStatus, NoWinnerPolicy, PredictionMarketAbi, PredictionMarketInstantiationArgument,
+    PredictionMarketMessage, PredictionMarketParameters, PriceCondition, Resolver, SurplusPolicy,
+};
+use price_feed::{PriceFeedAbi, PriceFeedMessage};
+
+const FEED_ID: &str = "BTC-USD";
+
+/// A yes/no market on whether the feed price ends above `threshold`, observed at `observation_time`
+fn price_market(feed: ApplicationId, threshold: u128, observation_time: Timestamp) -> PredictionMarketMessage {
+    PredictionMarketMessage::CreateMarket {
+        question: format!("Will {} be above {}?", FEED_ID, threshold),
+        description: String::new(),
+        resolution_criteria: "First feed price at the observation time".to_string(),
+        resolution_source: "Price feed application".to_string(),
+        duration_minutes: 1,
+        options: vec!["Yes".to_string(), "No".to_string()],
+        max_reward: Amount::from_tokens(1_000),
+        lock_before_end: None,
+        no_winner_policy: NoWinnerPolicy::RefundBettors,
+        surplus_policy: SurplusPolicy::RedistributeToWinners,
+        withdrawal_fee_bps: 0,
+        creator_fee_bps: 0,
+        resolver: Resolver::PriceFeed {
+            application_id: feed,
+            condition: PriceCondition {
+                feed_id: FEED_ID.to_string(),
+                comparator: Comparator::Above,
+                threshold,
+                observation_time,
+            },
+        },
+        dispute_settings: None,
+        resolution_deadline_minutes: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resolves_markets_from_a_published_price() {
+    let (validator, market_bytecode) = TestValidator::with_current_bytecode::<
+        PredictionMarketAbi,
+        PredictionMarketParameters,
+        PredictionMarketInstantiationArgument,
+    >()
+    .await;
+    let chain = validator.new_chain().await;
+
+    // The owner instantiating the feed becomes its reporter
+    let feed_bytecode = chain.publish_bytecodes_in("../price-feed").await;
+    let feed = chain
+        .create_application::<PriceFeedAbi, (), ()>(feed_bytecode.with_abi(), (), (), vec![])
+        .await;
+    let market_app = chain
+        .create_application(
+            market_bytecode,
+            PredictionMarketParameters::default(),
+            PredictionMarketInstantiationArgument::default(),
+            vec![feed.forget_abi()],
+        )
+        .await;
+
+    // Both markets end, and are observed, one minute from now
+    let observation_time = validator
+        .clock()
+        .current_time()
+        .saturating_add(TimeDelta::from_secs(60));
+    chain
+        .add_block(|block| {
+            block
+                .with_operation(market_app, price_market(feed.forget_abi(), 50_000, observation_time))
+                .with_operation(market_app, price_market(feed.forget_abi(), 70_000, observation_time));
+        })
+        .await;
+    let threshold_met = MarketId::new(chain.id(), 1);
+    let threshold_missed = MarketId::new(chain.id(), 2);
+
+    // Resolving before any price is published fails
+    validator.clock().add(TimeDelta::from_secs(120));
+    let attempt = chain
+        .try_add_block(|block| {
+            block.with_operation(
+                market_app,
+                PredictionMarketMessage::ResolveFromFeed {
+                    market_id: threshold_met,
+                },
+            );
+        })
+        .await;
+    assert!(attempt.is_err());
+
+    // Publish the price, then resolve both markets from it
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                feed,
+                PriceFeedMessage::Publish {
+                    feed_id: FEED_ID.to_string(),
+                    price: 60_000,
+                },
+            );
+        })
+        .await;
+    chain
+        .add_block(|block| {
+            block
+                .with_operation(
+                    market_app,
+                    PredictionMarketMessage::ResolveFromFeed {
+                        market_id: threshold_met,
+                    },
+                )
+                .with_operation(
+                    market_app,
+                    PredictionMarketMessage::ResolveFromFeed {
+                        market_id: threshold_missed,
+                    },
+                );
+        })
+        .await;
+
+    // Nobody bet, so both markets resolve without winners but record the observed answer
+    let state = snapshot(&chain, market_app).await;
+    let market = &state.markets[&threshold_met];
+    assert_eq!(market.status, MarketStatus::ResolvedNoWinners);
+    assert_eq!(market.correct_answer.as_deref(), Some("Yes"));
+    let market = &state.markets[&threshold_missed];
+    assert_eq!(market.status, MarketStatus::ResolvedNoWinners);
+    assert_eq!(market.correct_answer.as_deref(), Some("No"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pays_the_bettors_on_the_side_the_price_settles() {
+    let (validator, market_bytecode) = TestValidator::with_current_bytecode::<
+        PredictionMarketAbi,
+        PredictionMarketParameters,
+        PredictionMarketInstantiationArgument,
+    >()
+    .await;
+    let mut chain = validator.new_chain().await;
+    let feed_bytecode = chain.publish_bytecodes_in("../price-feed").await;
+    let feed = chain
+        .create_application::<PriceFeedAbi, (), ()>(feed_bytecode.with_abi(), (), (), vec![])
+        .await;
+    let market_app = chain
+        .create_application(
+            market_bytecode,
+            PredictionMarketParameters::default(),
+            PredictionMarketInstantiationArgument::default(),
+            vec![feed.forget_abi()],
+        )
+        .await;
+
+    // The chain owner instantiated the feed, and lets the first user publish on it
+    let funds = Amount::from_tokens(10);
+    let users = add_users(&chain, 4, funds).await;
+    let (reporter, alice, bob, carol) = (&users[0], &users[1], &users[2], &users[3]);
+    let observation_time = validator
+        .clock()
+        .current_time()
+        .saturating_add(TimeDelta::from_secs(60));
+    chain
+        .add_block(|block| {
+            block
+                .with_operation(feed, PriceFeedMessage::AddReporter { owner: reporter.owner })
+                .with_operation(market_app, price_market(feed.forget_abi(), 50_000, observation_time));
+        })
+        .await;
+    let market_id = MarketId::new(chain.id(), 1);
+
+    // 8 tokens on each side, split unevenly between the two bettors above the threshold
+    for (user, option, tokens) in [(alice, "Yes", 2), (bob, "Yes", 6), (carol, "No", 8)] {
+        add_block_as(&mut chain, user, |block| {
+            block.with_operation(
+                market_app,
+                PredictionMarketMessage::PlaceBet {
+                    market_id,
+                    option: option.to_string(),
+                    amount: Amount::from_tokens(tokens),
+                },
+            );
+        })
+        .await;
+    }
+
+    validator.clock().add(TimeDelta::from_secs(120));
+    add_block_as(&mut chain, reporter, |block| {
+        block
+            .with_operation(
+                feed,
+                PriceFeedMessage::Publish {
+                    feed_id: FEED_ID.to_string(),
+                    price: 60_000,
+                },
+            )
+            .with_operation(market_app, PredictionMarketMessage::ResolveFromFeed { market_id });
+    })
+    .await;
+
+    // The price is above the threshold, so the Yes bettors share the whole pool by stake
+    let state = snapshot(&chain, market_app).await;
+    let market = &state.markets[&market_id];
+    assert_eq!(market.status, MarketStatus::Resolved);
+    assert_eq!(market.correct_answer.as_deref(), Some("Yes"));
+    let reward_of = |owner| {
+        state
+            .positions
+            .iter()
+            .find(|position| position.market_id == market_id && position.user == owner)
+            .map(|position| position.reward_amount)
+    };
+    assert_eq!(reward_of(alice.owner), Some(Amount::from_tokens(4)));
+    assert_eq!(reward_of(bob.owner), Some(Amount::from_tokens(12)));
+    assert_eq!(reward_of(carol.owner), Some(Amount::ZERO));
+
+    for user in [alice, bob] {
+        add_block_as(&mut chain, user, |block| {
+            block.with_operation(market_app, PredictionMarketMessage::ClaimReward { market_id });
+        })
+        .await;
+    }
+    let claimed = try_add_block_as(&mut chain, carol, |block| {
+        block.with_operation(market_app, PredictionMarketMessage::ClaimReward { market_id });
+    })
+    .await;
+    assert!(!claimed);
+
+    assert_eq!(
+        balance(&chain, AccountOwner::User(alice.owner)).await,
+        funds.saturating_add(Amount::from_tokens(2))
+    );
+    assert_eq!(
+        balance(&chain, AccountOwner::User(bob.owner)).await,
+        funds.saturating_add(Amount::from_tokens(6))
+    );
+    assert_eq!(
+        balance(&chain, AccountOwner::User(carol.owner)).await,
+        funds.saturating_sub(Amount::from_tokens(8))
+    );
+    assert_eq!(escrow_balance(&chain, market_app).await, Amount::ZERO);
+}
@@ -0,0 +1,255 @@
+//! Price Feed Application for Linera
+//!
+//! A minimal price oracle: reporters publish prices on named feeds, and other applications
+//! on the same chain read them through a cross-application call. The prediction market
+//! uses it to resolve "will X be above Y at time T" markets; it can be driven locally by
+//! publishing prices from a test chain.
+
+use linera_sdk::base::{ContractAbi, Owner, ServiceAbi, Timestamp};
+use linera_sdk::views::{RootView, View};
+use linera_sdk::{Contract, Service};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+mod state;
+
+pub use state::{PriceFeedSnapshot, PriceFeedState, PriceObservation};
+
+/// The price feed application.
+pub struct PriceFeed {
+    state: PriceFeedState,
+}
+
+/// ABI of the price feed application.
+pub struct PriceFeedAbi;
+
+impl ContractAbi for PriceFeedAbi {
+    type Message = PriceFeedMessage;
+    type Effect = PriceFeedEffect;
+    type Parameters = ();
+    type InstantiationArgument = ();
+}
+
+/// Queries are answered with a JSON-serialized `PriceFeedSnapshot`.
+impl ServiceAbi for PriceFeedAbi {
+    type Query = String;
+    type QueryResponse = String;
+}
+
+/// Message for the price feed application.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PriceFeedMessage {
+    /// Publish the current price of a feed (reporters only)
+    Publish { feed_id: String, price: u128 },
+    /// Allow another owner to publish prices (reporters only)
+    AddReporter { owner: Owner },
+}
+
+/// Call made by other applications to read a feed.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PriceFeedCall {
+    /// First price published on `feed_id` at or after `time`
+    ObservationAt { feed_id: String, time: Timestamp },
+}
+
+/// Effect for the price feed application.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PriceFeedEffect {
+    /// Price published event
+    PricePublished {
+        feed_id: String,
+        price: u128,
+        timestamp: Timestamp,
+    },
+    /// Reporter added event
+    ReporterAdded { owner: Owner },
+}
+
+/// Every way a price feed operation can fail.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum PriceFeedError {
+    /// The signer is not a reporter
+    Unauthorized,
+    /// Feed ID is empty
+    EmptyFeedId,
+    /// A price was already published on the feed at the current time
+    AlreadyPublished,
+    /// Reading or writing the application state failed
+    Storage(String),
+}
+
+impl PriceFeedError {
+    /// Stable code identifying the error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::EmptyFeedId => "EMPTY_FEED_ID",
+            Self::AlreadyPublished => "ALREADY_PUBLISHED",
+            Self::Storage(_) => "STORAGE",
+        }
+    }
+}
+
+impl fmt::Display for PriceFeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.code())?;
+        match self {
+            Self::Unauthorized => write!(f, "Only reporters can publish prices"),
+            Self::EmptyFeedId => write!(f, "Feed ID must not be empty"),
+            Self::AlreadyPublished => write!(f, "A price was already published on this feed at this time"),
+            Self::Storage(reason) => write!(f, "Storage error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for PriceFeedError {}
+
+impl From<linera_sdk::views::ViewError> for PriceFeedError {
+    fn from(error: linera_sdk::views::ViewError) -> Self {
+        Self::Storage(error.to_string())
+    }
+}
+
+impl From<PriceFeedError> for linera_sdk::base::ContractError {
+    fn from(error: PriceFeedError) -> Self {
+        linera_sdk::base::ContractError::Other(error.to_string())
+    }
+}
+
+#[async_trait::async_trait]
+impl Contract for PriceFeed {
+    type Message = PriceFeedMessage;
+    type Parameters = ();
+    type InstantiationArgument = ();
+    type ApplicationCall = PriceFeedCall;
+    type CallResponse = Option<PriceObservation>;
+
+    async fn load(
+        context: &linera_sdk::base::ContractRuntimeContext,
+    ) -> Result<Self, linera_sdk::base::ContractError> {
+        let state = PriceFeedState::load(context.view_storage_context())
+            .await
+            .map_err(PriceFeedError::from)?;
+        Ok(PriceFeed { state })
+    }
+
+    async fn instantiate(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        _argument: Self::InstantiationArgument,
+    ) -> Result<(), linera_sdk::base::ContractError> {
+        // The owner instantiating the application becomes its first reporter
+        if let Some(reporter) = context.authenticated_signer() {
+            self.state.reporters.insert(&reporter).map_err(PriceFeedError::from)?;
+        }
+        Ok(())
+    }
+
+    async fn execute_message(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        message: Self::Message,
+    ) -> Result<Vec<PriceFeedEffect>, linera_sdk::base::ContractError> {
+        let effects = match message {
+            PriceFeedMessage::Publish { feed_id, price } => self.publish(context, feed_id, price).await,
+            PriceFeedMessage::AddReporter { owner } => self.add_reporter(context, owner).await,
+        };
+        effects.map_err(Into::into)
+    }
+
+    async fn execute_effect(
+        &mut self,
+        _context: &linera_sdk::base::ContractRuntimeContext,
+        _effect: PriceFeedEffect,
+    ) -> Result<Vec<PriceFeedEffect>, linera_sdk::base::ContractError> {
+        // Feeds are local to their chain; effects are events for clients only
+        Ok(vec![])
+    }
+
+    async fn handle_application_call(
+        &mut self,
+        _context: &linera_sdk::base::ContractRuntimeContext,
+        call: Self::ApplicationCall,
+    ) -> Result<Self::CallResponse, linera_sdk::base::ContractError> {
+        match call {
+            PriceFeedCall::ObservationAt { feed_id, time } => Ok(self
+                .state
+                .observation_at(&feed_id, time)
+                .await
+                .map_err(PriceFeedError::from)?),
+        }
+    }
+
+    async fn store(mut self) -> Result<(), linera_sdk::base::ContractError> {
+        self.state.save().await.map_err(PriceFeedError::from)?;
+        Ok(())
+    }
+}
+
+impl PriceFeed {
+    /// Append the current price to a feed
+    async fn publish(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        feed_id: String,
+        price: u128,
+    ) -> Result<Vec<PriceFeedEffect>, PriceFeedError> {
+        self.check_reporter(context).await?;
+        if feed_id.is_empty() {
+            return Err(PriceFeedError::EmptyFeedId);
+        }
+
+        // Keep the first price of each instant, the one markets observing it resolve from
+        let key = (feed_id, context.system_time());
+        if self.state.observations.get(&key).await?.is_some() {
+            return Err(PriceFeedError::AlreadyPublished);
+        }
+        self.state.observations.insert(&key, price)?;
+        let (feed_id, timestamp) = key;
+
+        Ok(vec![PriceFeedEffect::PricePublished {
+            feed_id,
+            price,
+            timestamp,
+        }])
+    }
+
+    /// Allow `owner` to publish prices
+    async fn add_reporter(
+        &mut self,
+        context: &linera_sdk::base::ContractRuntimeContext,
+        owner: Owner,
+    ) -> Result<Vec<PriceFeedEffect>, PriceFeedError> {
+        self.check_reporter(context).await?;
+        self.state.reporters.insert(&owner)?;
+        Ok(vec![PriceFeedEffect::ReporterAdded { owner }])
+    }
+
+    /// Fail unless the signer is a reporter
+    async fn check_reporter(&self, context: &linera_sdk::base::ContractRuntimeContext) -> Result<(), PriceFeedError> {
+        let signer = context.authenticated_signer().ok_or(PriceFeedError::Unauthorized)?;
+        if !self.state.reporters.contains(&signer).await? {
+            return Err(PriceFeedError::Unauthorized);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Service for PriceFeed {
+    type Query = String;
+    type QueryResponse = String;
+
+    async fn handle_query(
+        &self,
+        _context: &linera_sdk::base::ServiceRuntimeContext,
+        _query: Self::Query,
+    ) -> Result<Self::QueryResponse, linera_sdk::base::ContractError> {
+        // Return a serialized snapshot of every feed
+        let snapshot = self.state.snapshot().await.map_err(PriceFeedError::from)?;
+        match serde_json::to_string(&snapshot) {
+            Ok(json) => Ok(json),
+            Err(e) => Err(linera_sdk::base::ContractError::Other(format!("Serialization error: {}", e))),
+        }
+    }
+}
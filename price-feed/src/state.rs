@@ -0,0 +1,75 @@
+//! On-chain state of the price feed application
+
+use linera_sdk::base::{Owner, Timestamp};
+use linera_sdk::views::{MapView, RootView, SetView, ViewError, ViewStorageContext};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The state of the price feed application.
+#[derive(RootView)]
+#[view(context = "ViewStorageContext")]
+pub struct PriceFeedState {
+    /// Owners allowed to publish prices
+    pub reporters: SetView<Owner>,
+    /// Published prices indexed by feed ID and publication time (append-only)
+    pub observations: MapView<(String, Timestamp), u128>,
+}
+
+/// A price published on a feed
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct PriceObservation {
+    /// Price as a fixed-point integer; each feed picks its own number of decimals
+    pub price: u128,
+    /// Timestamp when the price was published
+    pub timestamp: Timestamp,
+}
+
+/// A serializable copy of every feed, returned by the service
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct PriceFeedSnapshot {
+    /// Published prices per feed ID, oldest first
+    pub observations: BTreeMap<String, Vec<PriceObservation>>,
+}
+
+impl PriceFeedState {
+    /// First price published on `feed_id` at or after `time`, if any.
+    ///
+    /// Publishing only adds one entry, so the feed is scanned here instead, when a market
+    /// resolves, rather than rewritten on every price.
+    pub async fn observation_at(
+        &self,
+        feed_id: &str,
+        time: Timestamp,
+    ) -> Result<Option<PriceObservation>, ViewError> {
+        let mut first: Option<PriceObservation> = None;
+        self.observations
+            .for_each_index_value(|(feed, timestamp), price| {
+                let earlier = first.as_ref().map_or(true, |observation| timestamp < observation.timestamp);
+                if feed == feed_id && timestamp >= time && earlier {
+                    first = Some(PriceObservation { price, timestamp });
+                }
+                Ok(())
+            })
+            .await?;
+        Ok(first)
+    }
+
+    /// Load every feed into a `PriceFeedSnapshot`
+    pub async fn snapshot(&self) -> Result<PriceFeedSnapshot, ViewError> {
+        let mut snapshot = PriceFeedSnapshot::default();
+        self.observations
+            .for_each_index_value(|(feed_id, timestamp), price| {
+                snapshot
+                    .observations
+                    .entry(feed_id)
+                    .or_default()
+                    .push(PriceObservation { price, timestamp });
+                Ok(())
+            })
+            .await?;
+        for observations in snapshot.observations.values_mut() {
+            observations.sort_by_key(|observation| observation.timestamp);
+        }
+        Ok(snapshot)
+    }
+}
@@ -17,6 +17,10 @@ if [ ! -f ~/.local/share/linera/wallet.json ]; then
     linera wallet request-chain --faucet="$LINERA_FAUCET_URL"
 fi
 
+echo "📦 Building price feed application..."
+cd /build/price-feed
+cargo build --release --target wasm32-unknown-unknown 2>&1 | head -50
+
 echo "📦 Building Linera contract..."
 cd /build/contract
 cargo build --release --target wasm32-unknown-unknown 2>&1 | head -50
@@ -3,9 +3,14 @@
 //! This service provides a GraphQL API for the prediction market application.
 //! It enables real-time queries and subscriptions for market data.
 
-use async_graphql::{Context, Object, Schema, Subscription};
+use async_graphql::{Context, ErrorExtensions, Object, Schema, Subscription};
 use linera_sdk::base::{Amount, Owner, Timestamp};
-use prediction_market::{Market as ContractMarket, Bet as ContractBet, PredictionMarketState};
+use prediction_market_abi::{
+    Bet as ContractBet, Claimable, Market as ContractMarket, MarketId, MarketSummary, NoWinnerPolicy,
+    OutgoingBet as ContractOutgoingBet, Position as ContractPosition,
+    PredictionMarketError, PredictionMarketQuery, PredictionMarketSnapshot, RegistryPage, Resolver, SurplusPolicy,
+};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::sync::Arc;
@@ -17,34 +22,58 @@ pub type PredictionMarketSchema = Schema<QueryRoot, MutationRoot, SubscriptionRo
 /// Query root for GraphQL
 pub struct QueryRoot;
 
+/// Default number of registry entries returned per page
+const DEFAULT_PAGE_SIZE: u32 = 50;
+/// Maximum number of registry entries returned per page
+const MAX_PAGE_SIZE: u32 = 200;
+
 #[Object]
 impl QueryRoot {
-    /// Get all markets
-    async fn markets(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<MarketResponse>> {
+    /// Page through the global market index kept by the registry chain.
+    ///
+    /// Only the requested page is read from the registry; on any other chain the index is empty.
+    async fn markets(
+        &self,
+        ctx: &Context<'_>,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> async_graphql::Result<MarketSummaryPage> {
+        // Ask the contract for the requested page only
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+        let page: RegistryPage = query_contract(
+            ctx,
+            &PredictionMarketQuery::RegistryPage {
+                offset: offset.into(),
+                limit: limit.into(),
+            },
+        )
+        .await?;
+
+        let next_offset = u64::from(offset) + page.items.len() as u64;
+        Ok(MarketSummaryPage {
+            total: page.total as u32,
+            next_offset: (next_offset < page.total).then_some(next_offset as u32),
+            items: page.items.iter().map(summary_to_response).collect(),
+        })
+    }
+
+    /// Get every market hosted on this chain
+    async fn local_markets(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<MarketResponse>> {
         // Get contract state from context
         // In a real implementation, this would query the Linera contract via service context
-        let state_str = ctx.data::<Arc<String>>().map(|s| s.as_str()).unwrap_or("");
-        
-        // Parse contract state
-        let state: PredictionMarketState = match serde_json::from_str(state_str) {
-            Ok(s) => s,
-            Err(_) => return Ok(vec![]), // Return empty if can't parse
-        };
-        
+        let state = load_snapshot(ctx)?;
+
         // Convert markets to responses
         Ok(state.markets.values().map(market_to_response).collect())
     }
 
     /// Get a market by ID
-    async fn market(&self, ctx: &Context<'_>, id: u64) -> async_graphql::Result<Option<MarketResponse>> {
+    async fn market(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<Option<MarketResponse>> {
+        let id = parse_market_id(&id)?;
+
         // Get contract state from context
-        let state_str = ctx.data::<Arc<String>>().map(|s| s.as_str()).unwrap_or("");
-        
-        // Parse contract state
-        let state: PredictionMarketState = match serde_json::from_str(state_str) {
-            Ok(s) => s,
-            Err(_) => return Ok(None),
-        };
+        let state = load_snapshot(ctx)?;
         
         // Find market by ID
         Ok(state.markets.get(&id).map(market_to_response))
@@ -54,62 +83,213 @@ impl QueryRoot {
     async fn user_bets(
         &self,
         ctx: &Context<'_>,
-        market_id: u64,
+        market_id: String,
         user: String,
     ) -> async_graphql::Result<Vec<BetResponse>> {
+        let market_id = parse_market_id(&market_id)?;
+
         // Get contract state from context
-        let state_str = ctx.data::<Arc<String>>().map(|s| s.as_str()).unwrap_or("");
-        
-        // Parse contract state
-        let state: PredictionMarketState = match serde_json::from_str(state_str) {
-            Ok(s) => s,
-            Err(_) => return Ok(vec![]),
-        };
+        let state = load_snapshot(ctx)?;
         
         // Parse user address (simplified - actual implementation would use proper Owner type)
         // For now, we'll match all bets for the market
-        let bets: Vec<BetResponse> = state.user_bets
+        let bets: Vec<BetResponse> = state.positions
             .iter()
-            .filter(|((mid, _), _)| *mid == market_id)
-            .map(|(_, bet)| bet_to_response(bet))
+            .filter(|position| position.market_id == market_id)
+            .flat_map(|position| position.bets.iter().map(bet_to_response))
             .collect();
         
         Ok(bets)
     }
 
-    /// Get claimable rewards for a user
-    async fn claimable_rewards(
+    /// Get a user's full position on a market
+    async fn position(
         &self,
         ctx: &Context<'_>,
+        market_id: String,
         user: String,
-    ) -> async_graphql::Result<Vec<RewardResponse>> {
+    ) -> async_graphql::Result<Option<PositionResponse>> {
+        let market_id = parse_market_id(&market_id)?;
+
+        // Get contract state from context
+        let state = load_snapshot(ctx)?;
+        
+        // Find the user's position on the market
+        Ok(state.positions
+            .iter()
+            .find(|position| position.market_id == market_id && format!("{:?}", position.user) == user)
+            .map(position_to_response))
+    }
+
+    /// Get all positions held by a user
+    async fn positions(
+        &self,
+        ctx: &Context<'_>,
+        user: String,
+    ) -> async_graphql::Result<Vec<PositionResponse>> {
         // Get contract state from context
-        let state_str = ctx.data::<Arc<String>>().map(|s| s.as_str()).unwrap_or("");
+        let state = load_snapshot(ctx)?;
         
-        // Parse contract state
-        let state: PredictionMarketState = match serde_json::from_str(state_str) {
-            Ok(s) => s,
-            Err(_) => return Ok(vec![]),
-        };
+        // Find all positions of the user
+        Ok(state.positions
+            .iter()
+            .filter(|position| format!("{:?}", position.user) == user)
+            .map(position_to_response)
+            .collect())
+    }
+
+    /// Get bets a user sent to markets on other chains that are not yet acknowledged
+    async fn outgoing_bets(
+        &self,
+        ctx: &Context<'_>,
+        user: String,
+    ) -> async_graphql::Result<Vec<OutgoingBetResponse>> {
+        // Get contract state from context
+        let state = load_snapshot(ctx)?;
         
-        // Find all claimable rewards for user
-        let rewards: Vec<RewardResponse> = state.user_bets
+        // Find all pending outgoing bets of the user
+        Ok(state.outgoing_bets
             .iter()
-            .filter(|(_, bet)| {
-                format!("{:?}", bet.user) == user 
-                    && bet.reward_amount > Amount::ZERO 
-                    && !bet.claimed
-            })
-            .map(|((market_id, _), bet)| RewardResponse {
-                market_id: *market_id,
-                user: user.clone(),
-                amount: bet.reward_amount.to_string(),
-                claimed: bet.claimed,
+            .filter(|bet| format!("{:?}", bet.user) == user)
+            .map(outgoing_bet_to_response)
+            .collect())
+    }
+
+    /// Get the bettor votes cast on a market resolved by vote
+    async fn votes(
+        &self,
+        ctx: &Context<'_>,
+        market_id: String,
+    ) -> async_graphql::Result<Vec<VoteResponse>> {
+        let market_id = parse_market_id(&market_id)?;
+
+        // Get contract state from context
+        let state = load_snapshot(ctx)?;
+
+        // Find all votes on the market
+        Ok(state.votes
+            .iter()
+            .filter(|vote| vote.market_id == market_id)
+            .map(|vote| VoteResponse {
+                market_id: vote.market_id.to_string(),
+                voter: format!("{:?}", vote.voter),
+                option: vote.option.clone(),
+                weight: vote.weight.to_string(),
+                timestamp: vote.timestamp.saturating_sub(Timestamp::from(0)) as i64,
             })
-            .collect();
+            .collect())
+    }
+
+    /// Get the application parameters shared by every chain
+    async fn parameters(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<ParametersResponse>> {
+        // Get contract state from context
+        let state = load_snapshot(ctx)?;
+
+        let parameters = &state.parameters;
+        Ok(Some(ParametersResponse {
+            registry_chain: parameters.registry_chain.map(|chain| chain.to_string()),
+            creator_bond: parameters.creator_bond.to_string(),
+            protocol_fee_bps: parameters.protocol_fee_bps,
+            max_creator_fee_bps: parameters.max_creator_fee_bps,
+            min_bet: parameters.min_bet.to_string(),
+            max_bet: parameters.max_bet.map(|amount| amount.to_string()),
+            max_options: parameters.max_options,
+            max_duration_minutes: parameters.max_duration_minutes,
+            admin: parameters.admin.map(|owner| format!("{:?}", owner)),
+            treasury: parameters.treasury.map(|owner| format!("{:?}", owner)),
+            token: parameters.token.map(|token| format!("{:?}", token)),
+        }))
+    }
+
+    /// Get claimable rewards and refunds for a user
+    async fn claimable_rewards(
+        &self,
+        ctx: &Context<'_>,
+        user: String,
+    ) -> async_graphql::Result<Vec<RewardResponse>> {
+        // Get contract state from context
+        let state = load_snapshot(ctx)?;
         
+        // Find all claimable rewards and refunds for user
+        let mut rewards = Vec::new();
+        for position in state.positions.iter().filter(|position| format!("{:?}", position.user) == user) {
+            let Some(market) = state.markets.get(&position.market_id) else {
+                continue;
+            };
+            let (amount, refund) = match position.claimable(market).map_err(|error| contract_error(&error))? {
+                Some(Claimable::Reward(amount)) => (amount, false),
+                Some(Claimable::Refund(amount)) => (amount, true),
+                None => continue,
+            };
+            // Refunds are never charged fees
+            let (protocol_fee, creator_fee) = match (&market.correct_answer, refund) {
+                (Some(answer), false) => market
+                    .fee_shares(position.stake_on(answer))
+                    .map_err(|error| contract_error(&error))?,
+                _ => (Amount::ZERO, Amount::ZERO),
+            };
+            rewards.push(RewardResponse {
+                market_id: position.market_id.to_string(),
+                user: user.clone(),
+                amount: amount.to_string(),
+                protocol_fee: protocol_fee.to_string(),
+                creator_fee: creator_fee.to_string(),
+                claimed: position.claimed,
+                refund,
+            });
+        }
+
         Ok(rewards)
     }
+
+    /// Admin settings of this chain
+    async fn admin(&self) -> AdminQuery {
+        AdminQuery
+    }
+
+    /// Get the protocol or creator fees accrued to an owner and not yet withdrawn
+    async fn fee_balance(
+        &self,
+        ctx: &Context<'_>,
+        owner: String,
+    ) -> async_graphql::Result<FeeBalanceResponse> {
+        // Get contract state from context
+        let state = load_snapshot(ctx)?;
+
+        // Owners without a balance have nothing to withdraw
+        let amount = state.fee_balances
+            .iter()
+            .find(|balance| format!("{:?}", balance.owner) == owner)
+            .map_or(Amount::ZERO, |balance| balance.amount);
+        Ok(FeeBalanceResponse {
+            owner,
+            amount: amount.to_string(),
+        })
+    }
+}
+
+/// Admin settings of a chain
+pub struct AdminQuery;
+
+#[Object]
+impl AdminQuery {
+    /// Get the admins of this chain, and the pause switches they control
+    async fn status(&self, ctx: &Context<'_>) -> async_graphql::Result<AdminStatusResponse> {
+        // Get contract state from context
+        let state = load_snapshot(ctx)?;
+
+        Ok(AdminStatusResponse {
+            admins: state.admins.iter().map(|owner| format!("{:?}", owner)).collect(),
+            application_admin: state.parameters.admin.map(|owner| format!("{:?}", owner)),
+            creation_paused: state.creation_paused,
+            betting_paused: state.betting_paused,
+            paused_markets: state.markets
+                .values()
+                .filter(|market| market.betting_paused)
+                .map(|market| market.id.to_string())
+                .collect(),
+        })
+    }
 }
 
 /// Mutation root for GraphQL
@@ -117,6 +297,11 @@ pub struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
+    /// Admin operations; the contract rejects them unless the signer is an admin
+    async fn admin(&self) -> AdminMutation {
+        AdminMutation
+    }
+
     /// Create a new prediction market
     async fn create_market(
         &self,
@@ -125,7 +310,7 @@ impl MutationRoot {
     ) -> async_graphql::Result<MarketResponse> {
         // Create market via contract message
         // This is a placeholder - actual implementation would send a message to the contract
-        Err(async_graphql::Error::new("Not implemented"))
+        Err(not_implemented())
     }
 
     /// Place a bet on a market option
@@ -136,10 +321,43 @@ impl MutationRoot {
     ) -> async_graphql::Result<BetResponse> {
         // Place bet via contract message
         // This is a placeholder - actual implementation would send a message to the contract
-        Err(async_graphql::Error::new("Not implemented"))
+        Err(not_implemented())
     }
 
-    /// Resolve a market (creator only)
+    /// Place a bet on a market hosted on another chain
+    async fn place_remote_bet(
+        &self,
+        ctx: &Context<'_>,
+        input: PlaceRemoteBetInput,
+    ) -> async_graphql::Result<OutgoingBetResponse> {
+        // Place remote bet via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Withdraw stake placed with `placeRemoteBet`; it is paid back on this chain
+    async fn withdraw_remote_bet(
+        &self,
+        ctx: &Context<'_>,
+        input: WithdrawBetInput,
+    ) -> async_graphql::Result<bool> {
+        // Withdraw remote bet via contract message; the market chain answers asynchronously
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Withdraw part or all of the stake on an option before the market locks
+    async fn withdraw_bet(
+        &self,
+        ctx: &Context<'_>,
+        input: WithdrawBetInput,
+    ) -> async_graphql::Result<PositionResponse> {
+        // Withdraw bet via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Resolve a market, or propose its answer when it has a challenge window (resolver only)
     async fn resolve_market(
         &self,
         ctx: &Context<'_>,
@@ -147,7 +365,84 @@ impl MutationRoot {
     ) -> async_graphql::Result<MarketResponse> {
         // Resolve market via contract message
         // This is a placeholder - actual implementation would send a message to the contract
-        Err(async_graphql::Error::new("Not implemented"))
+        Err(not_implemented())
+    }
+
+    /// Resolve a price-feed market from the feed price at its observation time
+    async fn resolve_from_feed(
+        &self,
+        ctx: &Context<'_>,
+        input: ResolveFromFeedInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Resolve from feed via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Vote on the correct answer of a market resolved by bettor vote
+    async fn cast_vote(
+        &self,
+        ctx: &Context<'_>,
+        input: CastVoteInput,
+    ) -> async_graphql::Result<VoteResponse> {
+        // Cast vote via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Vote with stake placed with `placeRemoteBet`
+    async fn cast_remote_vote(
+        &self,
+        ctx: &Context<'_>,
+        input: CastVoteInput,
+    ) -> async_graphql::Result<bool> {
+        // Cast remote vote via contract message; the market chain answers asynchronously
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Settle a market resolved by bettor vote once voting closed
+    async fn close_vote(
+        &self,
+        ctx: &Context<'_>,
+        input: CloseVoteInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Close vote via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Dispute a proposed answer, posting the market's bond
+    async fn dispute_resolution(
+        &self,
+        ctx: &Context<'_>,
+        input: DisputeResolutionInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Dispute resolution via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Make an undisputed proposed answer final after its challenge window
+    async fn finalize_resolution(
+        &self,
+        ctx: &Context<'_>,
+        input: FinalizeResolutionInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Finalize resolution via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Rule on a disputed answer (arbiter only)
+    async fn arbitrate_market(
+        &self,
+        ctx: &Context<'_>,
+        input: ArbitrateMarketInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Arbitrate market via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
     }
 
     /// Claim reward from a resolved market
@@ -158,7 +453,152 @@ impl MutationRoot {
     ) -> async_graphql::Result<RewardResponse> {
         // Claim reward via contract message
         // This is a placeholder - actual implementation would send a message to the contract
-        Err(async_graphql::Error::new("Not implemented"))
+        Err(not_implemented())
+    }
+
+    /// Claim the reward of stake placed with `placeRemoteBet`; it is paid on this chain
+    async fn claim_remote_reward(
+        &self,
+        ctx: &Context<'_>,
+        input: ClaimRewardInput,
+    ) -> async_graphql::Result<bool> {
+        // Claim remote reward via contract message; the market chain answers asynchronously
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Lock a market whose betting window has closed
+    async fn lock_market(
+        &self,
+        ctx: &Context<'_>,
+        input: LockMarketInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Lock market via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Cancel a market (creator before the first bet, or admin)
+    async fn cancel_market(
+        &self,
+        ctx: &Context<'_>,
+        input: CancelMarketInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Cancel market via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Expire a market left unresolved past its resolution deadline
+    async fn expire_market(
+        &self,
+        ctx: &Context<'_>,
+        input: ExpireMarketInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Expire market via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Claim back the stake placed on a cancelled or expired market
+    async fn claim_refund(
+        &self,
+        ctx: &Context<'_>,
+        input: ClaimRefundInput,
+    ) -> async_graphql::Result<RewardResponse> {
+        // Claim refund via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Claim the refund of stake placed with `placeRemoteBet`; it is paid on this chain
+    async fn claim_remote_refund(
+        &self,
+        ctx: &Context<'_>,
+        input: ClaimRefundInput,
+    ) -> async_graphql::Result<bool> {
+        // Claim remote refund via contract message; the market chain answers asynchronously
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Withdraw every protocol or creator fee accrued to the signer
+    async fn withdraw_fees(&self, ctx: &Context<'_>) -> async_graphql::Result<FeeBalanceResponse> {
+        // Withdraw fees via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+}
+
+/// Admin operations of a chain
+pub struct AdminMutation;
+
+#[Object]
+impl AdminMutation {
+    /// Pause or resume market creation
+    async fn set_creation_paused(
+        &self,
+        ctx: &Context<'_>,
+        paused: bool,
+    ) -> async_graphql::Result<AdminStatusResponse> {
+        // Pause creation via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Pause or resume betting on every market
+    async fn set_betting_paused(
+        &self,
+        ctx: &Context<'_>,
+        paused: bool,
+    ) -> async_graphql::Result<AdminStatusResponse> {
+        // Pause betting via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Pause or resume betting on one market
+    async fn set_market_betting_paused(
+        &self,
+        ctx: &Context<'_>,
+        input: SetMarketBettingPausedInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Pause market betting via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Cancel any unresolved market, returning the bonds of a pending proposal or dispute
+    async fn force_cancel_market(
+        &self,
+        ctx: &Context<'_>,
+        input: CancelMarketInput,
+    ) -> async_graphql::Result<MarketResponse> {
+        // Force-cancel market via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Grant admin rights
+    async fn add_admin(
+        &self,
+        ctx: &Context<'_>,
+        owner: String,
+    ) -> async_graphql::Result<AdminStatusResponse> {
+        // Add admin via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
+    }
+
+    /// Revoke admin rights; the last admin cannot be removed
+    async fn remove_admin(
+        &self,
+        ctx: &Context<'_>,
+        owner: String,
+    ) -> async_graphql::Result<AdminStatusResponse> {
+        // Remove admin via contract message
+        // This is a placeholder - actual implementation would send a message to the contract
+        Err(not_implemented())
     }
 }
 
@@ -171,7 +611,7 @@ impl SubscriptionRoot {
     async fn market_updates(
         &self,
         ctx: &Context<'_>,
-        market_id: u64,
+        market_id: String,
     ) -> impl Stream<Item = MarketResponse> {
         // Subscribe to market updates via contract events
         // This is a placeholder - actual implementation would subscribe to contract events
@@ -182,7 +622,7 @@ impl SubscriptionRoot {
     async fn bet_updates(
         &self,
         ctx: &Context<'_>,
-        market_id: u64,
+        market_id: String,
     ) -> impl Stream<Item = BetResponse> {
         // Subscribe to bet updates via contract events
         // This is a placeholder - actual implementation would subscribe to contract events
@@ -193,7 +633,7 @@ impl SubscriptionRoot {
     async fn pool_updates(
         &self,
         ctx: &Context<'_>,
-        market_id: u64,
+        market_id: String,
     ) -> impl Stream<Item = PoolUpdateResponse> {
         // Subscribe to pool updates via contract events
         // This is a placeholder - actual implementation would subscribe to contract events
@@ -205,20 +645,166 @@ impl SubscriptionRoot {
 
 #[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct MarketResponse {
-    pub id: u64,
+    /// Market ID in canonical `<chain id>:<index>` form
+    pub id: String,
     pub creator: String,
     pub question: String,
     pub description: String,
+    /// How the correct answer will be determined
+    pub resolution_criteria: String,
+    /// Where the resolver will look for the answer
+    pub resolution_source: String,
+    /// Evidence backing the resolved or proposed answer; the arbiter's once a dispute is ruled on
+    pub resolution_evidence: Option<EvidenceResponse>,
     pub end_time: i64,
+    pub lock_time: i64,
     pub status: String,
     pub options: Vec<String>,
     pub correct_answer: Option<String>,
     pub bets: Vec<OptionBetResponse>,
     pub total_pool: String,
     pub max_reward: String,
+    /// What happens to the pool if nobody wins: `RefundBettors` or `SendToTreasury`
+    pub no_winner_policy: String,
+    /// How the pool amount withheld by the `max_reward` cap is handled
+    pub surplus_policy: String,
+    /// Pool amount withheld by the `max_reward` cap
+    pub surplus: String,
+    /// Pool amount left in escrow by rounding rewards down
+    pub dust: String,
+    /// Creator bond still held in escrow
+    pub creator_bond: String,
+    /// Whether an admin paused betting on this market
+    pub betting_paused: bool,
+    /// Fee in basis points kept in the pool when a bet is withdrawn
+    pub withdrawal_fee_bps: u16,
+    /// Withdrawal fees kept in the pool
+    pub withdrawal_fees: String,
+    /// Protocol fee in basis points taken off the pool at resolution
+    pub protocol_fee_bps: u16,
+    /// Creator fee in basis points taken off the pool at resolution
+    pub creator_fee_bps: u16,
+    /// Protocol fee taken off the pool (zero until resolved)
+    pub protocol_fee: String,
+    /// Creator fee taken off the pool (zero until resolved)
+    pub creator_fee: String,
+    /// Who decides the correct answer
+    pub resolver: ResolverResponse,
+    /// Answers submitted so far by resolver committee members
+    pub resolution_votes: Vec<ResolutionVoteResponse>,
+    /// Minutes during which a proposed answer can be disputed (answers are final immediately when unset)
+    pub challenge_minutes: Option<u64>,
+    /// Bond posted by the proposer and by a disputer
+    pub dispute_bond: Option<String>,
+    /// Owner ruling on disputed answers
+    pub arbiter: Option<String>,
+    /// Answer proposed by the resolver, while not final
+    pub proposal: Option<ProposalResponse>,
+    /// End of the bettor voting window, for markets resolved by vote
+    pub voting_ends: Option<i64>,
+    /// Time after which the unresolved market can be expired and refunded
+    pub resolution_deadline: Option<i64>,
+    /// Stake-weighted bettor votes per option
+    pub vote_tally: Vec<OptionBetResponse>,
     pub created_at: i64,
 }
 
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct EvidenceResponse {
+    /// Evidence text or URL
+    pub source: String,
+    /// Hex-encoded hash of the evidence content
+    pub content_hash: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct ProposalResponse {
+    pub answer: String,
+    pub proposer: String,
+    pub proposed_at: i64,
+    pub challenge_ends: i64,
+    pub disputer: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct ResolverResponse {
+    /// `Creator`, `Owner`, `Committee`, `Oracle`, `BettorVote` or `PriceFeed`
+    pub kind: String,
+    /// Resolving owner, or committee members
+    pub owners: Vec<String>,
+    /// Number of agreeing committee members needed to resolve
+    pub quorum: Option<u32>,
+    /// Oracle or price-feed application resolving the market
+    pub application_id: Option<String>,
+    /// Minutes after the end time during which bettors vote
+    pub voting_minutes: Option<u64>,
+    /// Price condition of a `PriceFeed` market; the first option wins if it holds
+    pub price_condition: Option<PriceConditionResponse>,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct PriceConditionResponse {
+    pub feed_id: String,
+    /// `Above`, `AtOrAbove`, `Below` or `AtOrBelow`
+    pub comparator: String,
+    /// Threshold in the feed's fixed-point units
+    pub threshold: String,
+    pub observation_time: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct VoteResponse {
+    pub market_id: String,
+    pub voter: String,
+    pub option: String,
+    /// Voter's total stake on the market
+    pub weight: String,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct ParametersResponse {
+    /// Chain keeping the global market index
+    pub registry_chain: Option<String>,
+    /// Bond escrowed from every market creator
+    pub creator_bond: String,
+    pub protocol_fee_bps: u16,
+    pub max_creator_fee_bps: u16,
+    pub min_bet: String,
+    pub max_bet: Option<String>,
+    pub max_options: Option<u32>,
+    pub max_duration_minutes: Option<u64>,
+    /// Admin on every chain
+    pub admin: Option<String>,
+    /// Recipient of protocol fees
+    pub treasury: Option<String>,
+    /// Token stakes are paid in; none for the native token
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct ResolutionVoteResponse {
+    pub voter: String,
+    pub answer: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct MarketSummaryResponse {
+    pub market_id: String,
+    pub question: String,
+    pub status: String,
+    pub end_time: i64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct MarketSummaryPage {
+    pub items: Vec<MarketSummaryResponse>,
+    /// Number of markets in the whole index
+    pub total: u32,
+    /// Offset of the next page, if any
+    pub next_offset: Option<u32>,
+}
+
 #[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct OptionBetResponse {
     pub option: String,
@@ -227,26 +813,84 @@ pub struct OptionBetResponse {
 
 #[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct BetResponse {
-    pub market_id: u64,
+    pub market_id: String,
     pub user: String,
     pub option: String,
     pub amount: String,
     pub timestamp: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct OutgoingBetResponse {
+    pub market_id: String,
+    pub user: String,
+    pub option: String,
+    pub amount: String,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct WithdrawalResponse {
+    pub option: String,
+    pub amount: String,
+    pub fee: String,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct PositionResponse {
+    pub market_id: String,
+    pub user: String,
+    /// Chain the bets were placed from, where payouts are sent
+    pub origin: String,
+    /// Total stake per option
+    pub stakes: Vec<OptionBetResponse>,
+    pub total_stake: String,
+    /// Individual bets making up the position, oldest first
+    pub bets: Vec<BetResponse>,
+    /// Withdrawals from the position, oldest first
+    pub withdrawals: Vec<WithdrawalResponse>,
     pub claimed: bool,
     pub reward_amount: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct RewardResponse {
-    pub market_id: u64,
+    pub market_id: String,
     pub user: String,
+    /// Net amount paid out, after fees
     pub amount: String,
+    /// Part of the market's protocol fee charged to this reward
+    pub protocol_fee: String,
+    /// Part of the market's creator fee charged to this reward
+    pub creator_fee: String,
     pub claimed: bool,
+    /// Whether this is a returned stake rather than winnings
+    pub refund: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct AdminStatusResponse {
+    /// Admins of this chain
+    pub admins: Vec<String>,
+    /// Admin on every chain, from the application parameters
+    pub application_admin: Option<String>,
+    pub creation_paused: bool,
+    pub betting_paused: bool,
+    /// Markets with betting paused individually
+    pub paused_markets: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct FeeBalanceResponse {
+    pub owner: String,
+    /// Accrued fees not yet withdrawn
+    pub amount: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct PoolUpdateResponse {
-    pub market_id: u64,
+    pub market_id: String,
     pub total_pool: String,
     pub option_bets: Vec<OptionBetResponse>,
 }
@@ -257,27 +901,141 @@ pub struct PoolUpdateResponse {
 pub struct CreateMarketInput {
     pub question: String,
     pub description: String,
+    pub resolution_criteria: String,
+    pub resolution_source: String,
     pub duration_minutes: u64,
     pub options: Vec<String>,
     pub max_reward: String,
+    pub lock_before_end: Option<u64>,
+    /// `RefundBettors` (default) or `SendToTreasury`, paying the application treasury
+    pub no_winner_policy: Option<String>,
+    /// `RedistributeToWinners` (default), `ReturnToCreator` or `SendToTreasury`, paying the
+    /// application treasury
+    pub surplus_policy: Option<String>,
+    pub withdrawal_fee_bps: Option<u16>,
+    /// Capped by the `max_creator_fee_bps` parameter
+    pub creator_fee_bps: Option<u16>,
+    /// `Creator` (default), `Owner`, `Committee` or `Oracle`
+    pub resolver: Option<String>,
+    /// Resolving owner, or committee members
+    pub resolver_owners: Option<Vec<String>>,
+    pub resolver_quorum: Option<u32>,
+    pub resolver_application_id: Option<String>,
+    /// Voting window of `BettorVote` markets
+    pub voting_minutes: Option<u64>,
+    /// Price condition of `PriceFeed` markets, read from `resolver_application_id`
+    pub feed_id: Option<String>,
+    /// `Above`, `AtOrAbove`, `Below` or `AtOrBelow`
+    pub comparator: Option<String>,
+    pub threshold: Option<String>,
+    pub observation_time: Option<i64>,
+    /// Minutes after the end time by which the market must be resolved
+    pub resolution_deadline_minutes: Option<u64>,
+    /// Enables optimistic resolution together with `dispute_bond` and `arbiter`
+    pub challenge_minutes: Option<u64>,
+    pub dispute_bond: Option<String>,
+    pub arbiter: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct ExpireMarketInput {
+    pub market_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct ResolveFromFeedInput {
+    pub market_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct CastVoteInput {
+    pub market_id: String,
+    pub option: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct CloseVoteInput {
+    pub market_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct DisputeResolutionInput {
+    pub market_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct FinalizeResolutionInput {
+    pub market_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct ArbitrateMarketInput {
+    pub market_id: String,
+    pub correct_answer: String,
+    /// Evidence text or URL backing the ruling
+    pub evidence: String,
+    /// Hex-encoded hash of the evidence content
+    pub content_hash: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
 pub struct PlaceBetInput {
-    pub market_id: u64,
+    pub market_id: String,
+    pub option: String,
+    pub amount: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct PlaceRemoteBetInput {
+    pub market_id: String,
+    pub option: String,
+    pub amount: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct WithdrawBetInput {
+    pub market_id: String,
     pub option: String,
     pub amount: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
 pub struct ResolveMarketInput {
-    pub market_id: u64,
+    pub market_id: String,
     pub correct_answer: String,
+    /// Evidence text or URL backing the answer
+    pub evidence: String,
+    /// Hex-encoded hash of the evidence content
+    pub content_hash: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
 pub struct ClaimRewardInput {
-    pub market_id: u64,
+    pub market_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct LockMarketInput {
+    pub market_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct CancelMarketInput {
+    pub market_id: String,
+    /// Slash the creator bond to the bettors (admin only)
+    #[graphql(default)]
+    pub at_fault: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct SetMarketBettingPausedInput {
+    pub market_id: String,
+    pub paused: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct ClaimRefundInput {
+    pub market_id: String,
 }
 
 /// Create the GraphQL schema
@@ -286,15 +1044,97 @@ pub fn create_schema() -> PredictionMarketSchema {
         .finish()
 }
 
+// Helper functions to convert contract errors to GraphQL errors
+
+/// Convert a contract error into a GraphQL error carrying its stable code.
+///
+/// The code is exposed as `extensions.code` so clients can branch on the error kind.
+pub fn contract_error(error: &PredictionMarketError) -> async_graphql::Error {
+    let message = error.to_string();
+    let message = PredictionMarketError::split_code(&message)
+        .map(|(_, text)| text.to_string())
+        .unwrap_or_else(|| message.clone());
+    let code = error.code();
+    async_graphql::Error::new(message).extend_with(|_, e| e.set("code", code))
+}
+
+/// Convert an error message returned by the contract into a GraphQL error.
+///
+/// Messages produced by a `PredictionMarketError` keep their code in `extensions.code`;
+/// anything else is reported with the `INTERNAL` code.
+pub fn contract_error_message(message: &str) -> async_graphql::Error {
+    let (code, text) = PredictionMarketError::split_code(message).unwrap_or(("INTERNAL", message));
+    let code = code.to_string();
+    async_graphql::Error::new(text).extend_with(|_, e| e.set("code", code))
+}
+
+/// Access to the contract's query handler, provided by the host as schema data next to the
+/// snapshot. Queries needing only part of the state, like a registry page, go through it.
+#[async_trait::async_trait]
+pub trait ContractQuerier: Send + Sync {
+    /// Send a JSON-serialized `PredictionMarketQuery` and return the contract's JSON answer,
+    /// or its error message
+    async fn query(&self, query: String) -> Result<String, String>;
+}
+
+/// Send `query` to the contract and parse its answer
+async fn query_contract<T: DeserializeOwned>(
+    ctx: &Context<'_>,
+    query: &PredictionMarketQuery,
+) -> async_graphql::Result<T> {
+    let querier = ctx
+        .data::<Arc<dyn ContractQuerier>>()
+        .map_err(|_| contract_error_message("Contract queries are unavailable"))?;
+    let query = serde_json::to_string(query).map_err(|error| contract_error_message(&error.to_string()))?;
+    let response = querier.query(query).await.map_err(|message| contract_error_message(&message))?;
+    serde_json::from_str(&response)
+        .map_err(|error| contract_error_message(&format!("Invalid contract answer: {}", error)))
+}
+
+/// Parse the contract state snapshot handed to the service.
+///
+/// A chain without state yet reads as the empty snapshot. Anything else that is not a
+/// snapshot is reported as a coded error, keeping the code of a contract error message.
+fn load_snapshot(ctx: &Context<'_>) -> async_graphql::Result<PredictionMarketSnapshot> {
+    let state_str = ctx.data::<Arc<String>>().map(|s| s.as_str()).unwrap_or("");
+    if state_str.is_empty() {
+        return Ok(PredictionMarketSnapshot::default());
+    }
+    serde_json::from_str(state_str).map_err(|error| {
+        if PredictionMarketError::split_code(state_str).is_some() {
+            contract_error_message(state_str)
+        } else {
+            contract_error_message(&format!("Invalid contract state: {}", error))
+        }
+    })
+}
+
+/// Error returned by mutations that do not reach the contract yet
+fn not_implemented() -> async_graphql::Error {
+    async_graphql::Error::new("Not implemented").extend_with(|_, e| e.set("code", "NOT_IMPLEMENTED"))
+}
+
+/// Parse a market ID argument given in canonical `<chain id>:<index>` form
+fn parse_market_id(id: &str) -> async_graphql::Result<MarketId> {
+    id.parse().map_err(|error| contract_error(&error))
+}
+
 // Helper functions to convert contract types to GraphQL types
 
 fn market_to_response(market: &ContractMarket) -> MarketResponse {
     MarketResponse {
-        id: market.id,
+        id: market.id.to_string(),
         creator: format!("{:?}", market.creator),
         question: market.question.clone(),
         description: market.description.clone(),
+        resolution_criteria: market.resolution_criteria.clone(),
+        resolution_source: market.resolution_source.clone(),
+        resolution_evidence: market.resolution_evidence.as_ref().map(|evidence| EvidenceResponse {
+            source: evidence.source.clone(),
+            content_hash: evidence.content_hash.to_string(),
+        }),
         end_time: market.end_time.saturating_sub(Timestamp::from(0)) as i64,
+        lock_time: market.lock_time().saturating_sub(Timestamp::from(0)) as i64,
         status: format!("{:?}", market.status),
         options: market.options.clone(),
         correct_answer: market.correct_answer.clone(),
@@ -306,19 +1146,146 @@ fn market_to_response(market: &ContractMarket) -> MarketResponse {
             .collect(),
         total_pool: market.total_pool.to_string(),
         max_reward: market.max_reward.to_string(),
+        no_winner_policy: match &market.no_winner_policy {
+            NoWinnerPolicy::RefundBettors => "RefundBettors".to_string(),
+            NoWinnerPolicy::SendToTreasury => "SendToTreasury".to_string(),
+        },
+        surplus_policy: match &market.surplus_policy {
+            SurplusPolicy::RedistributeToWinners => "RedistributeToWinners".to_string(),
+            SurplusPolicy::ReturnToCreator => "ReturnToCreator".to_string(),
+            SurplusPolicy::SendToTreasury => "SendToTreasury".to_string(),
+        },
+        surplus: market.surplus.to_string(),
+        dust: market.dust.to_string(),
+        creator_bond: market.creator_bond.to_string(),
+        betting_paused: market.betting_paused,
+        withdrawal_fee_bps: market.withdrawal_fee_bps,
+        withdrawal_fees: market.withdrawal_fees.to_string(),
+        protocol_fee_bps: market.protocol_fee_bps,
+        creator_fee_bps: market.creator_fee_bps,
+        protocol_fee: market.protocol_fee.to_string(),
+        creator_fee: market.creator_fee.to_string(),
+        resolver: resolver_to_response(&market.resolver),
+        resolution_votes: market.resolution_votes.iter()
+            .map(|(voter, answer)| ResolutionVoteResponse {
+                voter: format!("{:?}", voter),
+                answer: answer.clone(),
+            })
+            .collect(),
+        challenge_minutes: market.dispute_settings.as_ref().map(|settings| settings.challenge_minutes),
+        dispute_bond: market.dispute_settings.as_ref().map(|settings| settings.bond.to_string()),
+        arbiter: market.dispute_settings.as_ref().map(|settings| format!("{:?}", settings.arbiter)),
+        proposal: market.proposal.as_ref().map(|proposal| ProposalResponse {
+            answer: proposal.answer.clone(),
+            proposer: format!("{:?}", proposal.proposer),
+            proposed_at: proposal.proposed_at.saturating_sub(Timestamp::from(0)) as i64,
+            challenge_ends: proposal.challenge_ends.saturating_sub(Timestamp::from(0)) as i64,
+            disputer: proposal.disputer.map(|disputer| format!("{:?}", disputer)),
+        }),
+        voting_ends: market.voting_ends().map(|time| time.saturating_sub(Timestamp::from(0)) as i64),
+        resolution_deadline: market.resolution_deadline.map(|time| time.saturating_sub(Timestamp::from(0)) as i64),
+        vote_tally: market.vote_tally.iter()
+            .map(|(option, weight)| OptionBetResponse {
+                option: option.clone(),
+                amount: weight.to_string(),
+            })
+            .collect(),
         created_at: market.created_at.saturating_sub(Timestamp::from(0)) as i64,
     }
 }
 
+fn resolver_to_response(resolver: &Resolver) -> ResolverResponse {
+    match resolver {
+        Resolver::Creator => ResolverResponse {
+            kind: "Creator".to_string(),
+            ..ResolverResponse::default()
+        },
+        Resolver::Owner { owner } => ResolverResponse {
+            kind: "Owner".to_string(),
+            owners: vec![format!("{:?}", owner)],
+            ..ResolverResponse::default()
+        },
+        Resolver::Committee { members, quorum } => ResolverResponse {
+            kind: "Committee".to_string(),
+            owners: members.iter().map(|member| format!("{:?}", member)).collect(),
+            quorum: Some(*quorum),
+            ..ResolverResponse::default()
+        },
+        Resolver::Oracle { application_id } => ResolverResponse {
+            kind: "Oracle".to_string(),
+            application_id: Some(format!("{:?}", application_id)),
+            ..ResolverResponse::default()
+        },
+        Resolver::BettorVote { voting_minutes } => ResolverResponse {
+            kind: "BettorVote".to_string(),
+            voting_minutes: Some(*voting_minutes),
+            ..ResolverResponse::default()
+        },
+        Resolver::PriceFeed { application_id, condition } => ResolverResponse {
+            kind: "PriceFeed".to_string(),
+            application_id: Some(format!("{:?}", application_id)),
+            price_condition: Some(PriceConditionResponse {
+                feed_id: condition.feed_id.clone(),
+                comparator: format!("{:?}", condition.comparator),
+                threshold: condition.threshold.to_string(),
+                observation_time: condition.observation_time.saturating_sub(Timestamp::from(0)) as i64,
+            }),
+            ..ResolverResponse::default()
+        },
+    }
+}
+
+fn summary_to_response(summary: &MarketSummary) -> MarketSummaryResponse {
+    MarketSummaryResponse {
+        market_id: summary.market_id.to_string(),
+        question: summary.question.clone(),
+        status: format!("{:?}", summary.status),
+        end_time: summary.end_time.saturating_sub(Timestamp::from(0)) as i64,
+    }
+}
+
 fn bet_to_response(bet: &ContractBet) -> BetResponse {
     BetResponse {
-        market_id: bet.market_id,
+        market_id: bet.market_id.to_string(),
         user: format!("{:?}", bet.user),
         option: bet.option.clone(),
         amount: bet.amount.to_string(),
         timestamp: bet.timestamp.saturating_sub(Timestamp::from(0)) as i64,
-        claimed: bet.claimed,
-        reward_amount: bet.reward_amount.to_string(),
     }
 }
 
+fn position_to_response(position: &ContractPosition) -> PositionResponse {
+    PositionResponse {
+        market_id: position.market_id.to_string(),
+        user: format!("{:?}", position.user),
+        origin: position.origin.to_string(),
+        stakes: position.stakes.iter()
+            .map(|(option, amount)| OptionBetResponse {
+                option: option.clone(),
+                amount: amount.to_string(),
+            })
+            .collect(),
+        total_stake: position.total_stake().to_string(),
+        bets: position.bets.iter().map(bet_to_response).collect(),
+        withdrawals: position.withdrawals.iter()
+            .map(|withdrawal| WithdrawalResponse {
+                option: withdrawal.option.clone(),
+                amount: withdrawal.amount.to_string(),
+                fee: withdrawal.fee.to_string(),
+                timestamp: withdrawal.timestamp.saturating_sub(Timestamp::from(0)) as i64,
+            })
+            .collect(),
+        claimed: position.claimed,
+        reward_amount: position.reward_amount.to_string(),
+    }
+}
+
+fn outgoing_bet_to_response(bet: &ContractOutgoingBet) -> OutgoingBetResponse {
+    OutgoingBetResponse {
+        market_id: bet.market_id.to_string(),
+        user: format!("{:?}", bet.user),
+        option: bet.option.clone(),
+        amount: bet.amount.to_string(),
+        timestamp: bet.timestamp.saturating_sub(Timestamp::from(0)) as i64,
+    }
+}